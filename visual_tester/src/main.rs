@@ -7,7 +7,7 @@ use opencv::{
 use std::env;
 use std::sync::{Arc, Mutex};
 use tokio::task::JoinSet;
-use waldo_vision::pipeline::{ChunkStatus, FrameAnalysis, PipelineConfig, TrackedBlob, TrackedState, VisionPipeline};
+use waldo_vision::pipeline::{AssociationWeights, BitDepth, BlobAlgorithm, ChunkStatus, FrameAnalysis, FrameGeometry, HeatFunction, default_blob_filters, JpegScale, KalmanConfig, LuminanceStandard, MorphologyConfig, MotionModel, PerspectiveModel, PipelineConfig, TrackAssignment, TrackedBlob, TrackedState, TrackerConfig, VisionPipeline};
 
 #[tokio::main]
 async fn main() -> opencv::Result<()> {
@@ -42,10 +42,26 @@ async fn main() -> opencv::Result<()> {
         image_height: frame_height,
         chunk_width: 10,
         chunk_height: 10,
-        new_age_threshold: 5,
-        behavioral_anomaly_threshold: 3.0,
-        absolute_min_blob_size: 2,
-        blob_size_std_dev_filter: 2.0,
+        luminance_standard: LuminanceStandard::Bt601,
+        linear_light: false,
+        high_bit_depth: BitDepth::default(),
+        jpeg_decode_scale: JpegScale::Full,
+        morphology: MorphologyConfig::default(),
+        blob_algorithm: BlobAlgorithm::PeakRegionGrowing,
+        heat_function: HeatFunction::Luminance,
+        peak_threshold: 1.0,
+        growth_threshold: 1.0,
+        predictable_motion_weight: None,
+        foreground_refinement: None,
+        tracker: TrackerConfig {
+            assignment: TrackAssignment::Hungarian,
+            motion_model: MotionModel::Kalman(KalmanConfig::default()),
+            association_weights: AssociationWeights { size: 0.5, ..AssociationWeights::default() },
+            frame_rate: if fps > 0.0 { fps } else { 30.0 },
+            ..TrackerConfig::default()
+        },
+        perspective: PerspectiveModel::Uniform,
+        blob_filters: default_blob_filters(),
         disturbance_entry_threshold: 0.25,
         disturbance_exit_threshold: 0.15,
         disturbance_confirmation_frames: 5,
@@ -70,7 +86,7 @@ async fn main() -> opencv::Result<()> {
             let mut heatmap_overlay = Mat::new_size_with_default(frame.size().unwrap(), core::CV_8UC3, Scalar::all(0.0)).unwrap();
             
             apply_dimming_and_heat(&mut output_frame, &mut heatmap_overlay, &analysis.status_map, frame_width, config_clone.chunk_width, config_clone.chunk_height);
-            draw_tracked_blobs(&mut output_frame, &analysis.tracked_blobs, config_clone.chunk_width, config_clone.chunk_height);
+            draw_tracked_blobs(&mut output_frame, &analysis.tracked_blobs, &analysis.geometry);
             draw_header(&mut output_frame, i, &analysis);
 
             let mut final_frame = Mat::default();
@@ -110,24 +126,25 @@ fn draw_header(frame: &mut Mat, frame_index: usize, analysis: &FrameAnalysis) {
 }
 
 // Other helper functions remain the same...
-fn draw_tracked_blobs(frame: &mut Mat, tracked_blobs: &[TrackedBlob], chunk_w: u32, chunk_h: u32) {
+fn draw_tracked_blobs(frame: &mut Mat, tracked_blobs: &[TrackedBlob], geometry: &FrameGeometry) {
     for blob in tracked_blobs {
         let color = state_to_color(&blob.state);
         for point in &blob.latest_blob.chunk_coords {
-            let top_left = core::Point::new(point.x as i32 * chunk_w as i32, point.y as i32 * chunk_h as i32);
-            let rect = Rect::new(top_left.x, top_left.y, chunk_w as i32, chunk_h as i32);
+            let chunk_box = geometry.chunk_to_pixel_box(*point);
+            let rect = Rect::new(chunk_box.min.x as i32, chunk_box.min.y as i32, chunk_box.width() as i32, chunk_box.height() as i32);
             let roi = Mat::roi(frame, rect).unwrap();
             let mut colored_roi = Mat::default();
             let color_mat = Mat::new_size_with_default(roi.size().unwrap(), roi.typ(), color).unwrap();
             core::add_weighted(&roi, 0.5, &color_mat, 0.5, 0.0, &mut colored_roi, -1).unwrap();
             colored_roi.copy_to(&mut Mat::roi(frame, rect).unwrap()).unwrap();
         }
-        let (top_left_p, bottom_right_p) = blob.latest_blob.bounding_box;
-        let top_left = core::Point::new(top_left_p.x as i32 * chunk_w as i32, top_left_p.y as i32 * chunk_h as i32);
-        let bottom_right = core::Point::new((bottom_right_p.x + 1) as i32 * chunk_w as i32, (bottom_right_p.y + 1) as i32 * chunk_h as i32);
-        let rect = Rect::new(top_left.x, top_left.y, bottom_right.x - top_left.x, bottom_right.y - top_left.y);
+        let bounding_box = blob.latest_blob.pixel_bounding_box(geometry);
+        let rect = Rect::new(bounding_box.min.x as i32, bounding_box.min.y as i32, bounding_box.width() as i32, bounding_box.height() as i32);
         imgproc::rectangle(frame, rect, color, 2, imgproc::LINE_8, 0).unwrap();
-        let label = format!("ID: {} | S: {:?} | A: {}", blob.id, blob.state, blob.age);
+        let label = match blob.anomaly_reasons.first() {
+            Some(reason) => format!("ID: {} | {} | A: {}", blob.id, reason.kind.description(), blob.age),
+            None => format!("ID: {} | S: {:?} | A: {}", blob.id, blob.state, blob.age),
+        };
         let text_pos = core::Point::new(rect.x, rect.y - 10);
         imgproc::put_text(frame, &label, text_pos, imgproc::FONT_HERSHEY_SIMPLEX, 0.5, color, 2, imgproc::LINE_AA, false).unwrap();
     }
@@ -0,0 +1,179 @@
+// THEORY:
+// The `appearance` module gives each blob a compact description of what it looks
+// like, not just where it is and how big it is. Position and size alone cannot tell
+// a red car from a white van when their paths cross; the colors under the blob can.
+//
+// Key architectural principles:
+// 1.  **Joint HSV Histogram**: The signature is a normalized histogram over hue,
+//     saturation and value with a few bins per channel. HSV separates color from
+//     brightness, so the signature holds up better under lighting changes than RGB.
+// 2.  **Computed From Frame Pixels**: The histogram is built from the actual pixels
+//     of the frame that lie under the blob's chunks, read through a `FrameView` so
+//     every input layout the pipeline accepts is supported.
+// 3.  **Comparable Signatures**: Bhattacharyya and chi-square distances are provided
+//     so later stages (tracking, re-identification) can compare two blobs directly.
+// 4.  **Stateless Utility**: Like the `BlobDetector`, it has no memory between frames.
+
+use crate::core_modules::grid_manager::FrameView;
+use crate::core_modules::smart_blob::Point;
+
+/// The number of hue bins, each covering 45 degrees.
+pub const HUE_BINS: usize = 8;
+/// The number of saturation bins.
+pub const SATURATION_BINS: usize = 4;
+/// The number of value (brightness) bins.
+pub const VALUE_BINS: usize = 4;
+/// The total number of bins in a `ColorHistogram`.
+pub const HISTOGRAM_BINS: usize = HUE_BINS * SATURATION_BINS * VALUE_BINS;
+
+/// A normalized joint HSV color histogram. The bins sum to 1.0, or are all zero
+/// when the histogram was built from no pixels.
+#[derive(Debug, Clone, PartialEq)]
+pub struct ColorHistogram {
+    /// Bin weights, indexed as `(hue * SATURATION_BINS + saturation) * VALUE_BINS + value`.
+    pub bins: Vec<f32>,
+}
+
+impl ColorHistogram {
+    /// Builds a histogram from hue (degrees), saturation (0-1) and value (0-1) triples.
+    pub fn from_hsv<I>(pixels: I) -> Self
+    where
+        I: IntoIterator<Item = (f32, f32, f32)>,
+    {
+        let mut bins = vec![0.0f32; HISTOGRAM_BINS];
+        let mut total = 0.0f32;
+        for (hue, saturation, value) in pixels {
+            let hue_bin = ((hue / 360.0 * HUE_BINS as f32) as usize).min(HUE_BINS - 1);
+            let saturation_bin = ((saturation * SATURATION_BINS as f32) as usize).min(SATURATION_BINS - 1);
+            let value_bin = ((value * VALUE_BINS as f32) as usize).min(VALUE_BINS - 1);
+            bins[(hue_bin * SATURATION_BINS + saturation_bin) * VALUE_BINS + value_bin] += 1.0;
+            total += 1.0;
+        }
+        if total > 0.0 {
+            bins.iter_mut().for_each(|bin| *bin /= total);
+        }
+        Self { bins }
+    }
+
+    /// Builds the histogram of every frame pixel covered by the given chunks.
+    pub fn from_chunks(
+        frame: &FrameView,
+        chunk_coords: &[Point],
+        image_width: u32,
+        chunk_width: u32,
+        chunk_height: u32,
+    ) -> Self {
+        let pixels = chunk_coords.iter().flat_map(|chunk| {
+            let (left, top) = (chunk.x * chunk_width, chunk.y * chunk_height);
+            (top..top + chunk_height)
+                .flat_map(move |y| (left..left + chunk_width).map(move |x| (x, y)))
+                .map(|(x, y)| frame.pixel_at(x, y, image_width).hsv())
+        });
+        Self::from_hsv(pixels)
+    }
+
+    /// The histogram of the combined pixels of two histograms, where `other_share` is
+    /// the fraction (0-1) of the combined pixels that `other` was built from.
+    pub fn combine(&self, other: &ColorHistogram, other_share: f32) -> Self {
+        let bins = self
+            .bins
+            .iter()
+            .zip(&other.bins)
+            .map(|(p, q)| p * (1.0 - other_share) + q * other_share)
+            .collect();
+        Self { bins }
+    }
+
+    /// The Bhattacharyya distance `sqrt(1 - sum(sqrt(p * q)))`, from 0.0 for identical
+    /// histograms to 1.0 for histograms with no overlap.
+    pub fn bhattacharyya_distance(&self, other: &ColorHistogram) -> f64 {
+        let coefficient: f64 = self
+            .bins
+            .iter()
+            .zip(&other.bins)
+            .map(|(p, q)| (*p as f64 * *q as f64).sqrt())
+            .sum();
+        (1.0 - coefficient).max(0.0).sqrt()
+    }
+
+    /// The symmetric chi-square distance `0.5 * sum((p - q)^2 / (p + q))`, from 0.0 for
+    /// identical histograms to 1.0 for histograms with no overlap.
+    pub fn chi_square_distance(&self, other: &ColorHistogram) -> f64 {
+        0.5 * self
+            .bins
+            .iter()
+            .zip(&other.bins)
+            .filter(|(p, q)| **p + **q > 0.0)
+            .map(|(p, q)| {
+                let (p, q) = (*p as f64, *q as f64);
+                (p - q).powi(2) / (p + q)
+            })
+            .sum::<f64>()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn assert_close(actual: f64, expected: f64) {
+        assert!((actual - expected).abs() < 1e-6, "{actual} != {expected}");
+    }
+
+    /// A histogram of red, green and blue pixels in the given counts.
+    fn mixed(red: usize, green: usize, blue: usize) -> ColorHistogram {
+        let pixels = [(0.0, 1.0, 1.0), (120.0, 1.0, 1.0), (240.0, 1.0, 1.0)];
+        ColorHistogram::from_hsv(
+            [red, green, blue]
+                .into_iter()
+                .zip(pixels)
+                .flat_map(|(count, pixel)| std::iter::repeat_n(pixel, count)),
+        )
+    }
+
+    #[test]
+    fn bins_are_normalized_and_clamped() {
+        let histogram = ColorHistogram::from_hsv([(0.0, 0.0, 0.0), (360.0, 1.0, 1.0), (359.0, 1.0, 1.0), (90.0, 0.5, 0.5)]);
+        assert_close(histogram.bins.iter().sum::<f32>() as f64, 1.0);
+        assert_eq!(histogram.bins[0], 0.25);
+        // Hue 360 and full saturation and value fall into the last bins, not past them.
+        assert_eq!(histogram.bins[HISTOGRAM_BINS - 1], 0.5);
+        assert_eq!(histogram.bins[(2 * SATURATION_BINS + 2) * VALUE_BINS + 2], 0.25);
+    }
+
+    #[test]
+    fn identical_histograms_are_at_distance_zero() {
+        let histogram = mixed(3, 5, 2);
+        assert_close(histogram.bhattacharyya_distance(&histogram.clone()), 0.0);
+        assert_close(histogram.chi_square_distance(&histogram.clone()), 0.0);
+        assert_close(mixed(1, 1, 0).bhattacharyya_distance(&mixed(4, 4, 0)), 0.0);
+    }
+
+    #[test]
+    fn disjoint_histograms_are_at_the_maximum_distance() {
+        let (red, blue) = (mixed(4, 0, 0), mixed(0, 0, 4));
+        assert_close(red.bhattacharyya_distance(&blue), 1.0);
+        assert_close(red.chi_square_distance(&blue), 1.0);
+
+        // Half of the pixels shared: sum(sqrt(p * q)) = 0.5 and chi-square counts the
+        // two unshared halves.
+        let (red_green, green_blue) = (mixed(1, 1, 0), mixed(0, 1, 1));
+        assert_close(red_green.bhattacharyya_distance(&green_blue), 0.5f64.sqrt());
+        assert_close(red_green.chi_square_distance(&green_blue), 0.5);
+    }
+
+    #[test]
+    fn empty_and_black_inputs_stay_finite() {
+        let empty = ColorHistogram::from_hsv(std::iter::empty());
+        assert!(empty.bins.iter().all(|bin| *bin == 0.0));
+        assert_close(empty.chi_square_distance(&empty), 0.0);
+        assert_close(empty.bhattacharyya_distance(&empty), 1.0);
+        assert!(empty.chi_square_distance(&mixed(1, 0, 0)).is_finite());
+
+        let black = [0u8, 0, 0, 255].repeat(16);
+        let histogram = ColorHistogram::from_chunks(&FrameView::Rgba8(&black), &[Point { x: 0, y: 0 }], 4, 4, 4);
+        assert!(histogram.bins.iter().all(|bin| bin.is_finite()));
+        assert_eq!(histogram.bins[0], 1.0);
+        assert_close(histogram.bhattacharyya_distance(&histogram), 0.0);
+    }
+}
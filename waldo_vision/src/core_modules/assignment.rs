@@ -0,0 +1,210 @@
+// THEORY:
+// The `assignment` module solves the "who is who" step of tracking as one global
+// optimization. Given a cost for pairing every existing track with every new
+// detection, it finds the pairing with the lowest total cost, instead of letting
+// each track grab its favorite detection in turn.
+//
+// Key architectural principles:
+// 1.  **Hungarian Method**: It implements the O(n^3) Hungarian algorithm with row and
+//     column potentials (shortest augmenting paths), on rectangular matrices.
+// 2.  **Forbidden Pairs**: A pair can be ruled out (for example, because the detection
+//     is too far from the track's prediction) by giving it an infinite cost. Such
+//     pairs are never returned, even if that leaves a row unassigned.
+// 3.  **Stateless Utility**: It knows nothing about blobs or tracks; it only sees a
+//     matrix of numbers.
+
+/// Finds the minimum-cost assignment of rows to columns. `costs[row][column]` is the
+/// cost of pairing them; non-finite costs mark forbidden pairs. Returns, for each row,
+/// the column assigned to it, if any. Every column is used at most once.
+pub fn solve(costs: &[Vec<f64>]) -> Vec<Option<usize>> {
+    let rows = costs.len();
+    let columns = costs.first().map_or(0, |row| row.len());
+    if rows == 0 || columns == 0 {
+        return vec![None; rows];
+    }
+
+    // The solver below needs at least as many columns as rows, so tall matrices are
+    // solved transposed.
+    if rows > columns {
+        let transposed: Vec<Vec<f64>> = (0..columns).map(|c| (0..rows).map(|r| costs[r][c]).collect()).collect();
+        let mut assignment = vec![None; rows];
+        for (column, row) in solve(&transposed).into_iter().enumerate() {
+            if let Some(row) = row {
+                assignment[row] = Some(column);
+            }
+        }
+        return assignment;
+    }
+
+    // Forbidden pairs get a cost larger than any complete assignment of allowed pairs,
+    // so they are only chosen when a row has no allowed column left.
+    let largest = costs.iter().flatten().filter(|c| c.is_finite()).fold(0.0f64, |a, c| a.max(c.abs()));
+    let forbidden = (largest + 1.0) * (rows + 1) as f64;
+    let cost = |row: usize, column: usize| {
+        let value = costs[row][column];
+        if value.is_finite() { value } else { forbidden }
+    };
+
+    // 1-based indices; row 0 and column 0 are the virtual start of each augmenting path.
+    let mut row_potential = vec![0.0; rows + 1];
+    let mut column_potential = vec![0.0; columns + 1];
+    let mut column_owner = vec![0usize; columns + 1];
+    let mut previous_column = vec![0usize; columns + 1];
+
+    for row in 1..=rows {
+        column_owner[0] = row;
+        let mut current_column = 0;
+        let mut min_slack = vec![f64::INFINITY; columns + 1];
+        let mut used = vec![false; columns + 1];
+
+        loop {
+            used[current_column] = true;
+            let current_row = column_owner[current_column];
+            let mut delta = f64::INFINITY;
+            let mut next_column = 0;
+            for column in 1..=columns {
+                if used[column] {
+                    continue;
+                }
+                let slack = cost(current_row - 1, column - 1) - row_potential[current_row] - column_potential[column];
+                if slack < min_slack[column] {
+                    min_slack[column] = slack;
+                    previous_column[column] = current_column;
+                }
+                if min_slack[column] < delta {
+                    delta = min_slack[column];
+                    next_column = column;
+                }
+            }
+            for column in 0..=columns {
+                if used[column] {
+                    row_potential[column_owner[column]] += delta;
+                    column_potential[column] -= delta;
+                } else {
+                    min_slack[column] -= delta;
+                }
+            }
+            current_column = next_column;
+            if column_owner[current_column] == 0 {
+                break;
+            }
+        }
+
+        // Flip the augmenting path.
+        loop {
+            let column = previous_column[current_column];
+            column_owner[current_column] = column_owner[column];
+            current_column = column;
+            if current_column == 0 {
+                break;
+            }
+        }
+    }
+
+    let mut assignment = vec![None; rows];
+    for column in 1..=columns {
+        let row = column_owner[column];
+        if row != 0 && costs[row - 1][column - 1].is_finite() {
+            assignment[row - 1] = Some(column - 1);
+        }
+    }
+    assignment
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const FORBIDDEN: f64 = f64::INFINITY;
+
+    fn total_cost(costs: &[Vec<f64>], assignment: &[Option<usize>]) -> f64 {
+        assignment.iter().enumerate().filter_map(|(row, column)| column.map(|c| costs[row][c])).sum()
+    }
+
+    /// The cheapest cost of assigning every row of a square matrix, by trying every permutation.
+    fn brute_force(costs: &[Vec<f64>]) -> f64 {
+        fn search(costs: &[Vec<f64>], row: usize, used: &mut Vec<bool>) -> f64 {
+            if row == costs.len() {
+                return 0.0;
+            }
+            let mut best = f64::INFINITY;
+            for column in 0..costs.len() {
+                if !used[column] {
+                    used[column] = true;
+                    best = best.min(costs[row][column] + search(costs, row + 1, used));
+                    used[column] = false;
+                }
+            }
+            best
+        }
+        search(costs, 0, &mut vec![false; costs.len()])
+    }
+
+    #[test]
+    fn empty_matrices() {
+        assert!(solve(&[]).is_empty());
+        assert_eq!(solve(&[Vec::new(), Vec::new()]), vec![None, None]);
+    }
+
+    #[test]
+    fn square_matrix_beats_greedy_choice() {
+        // Row 0 taking its cheapest column would leave row 1 with a cost of 10.
+        let costs = vec![vec![1.0, 2.0], vec![1.0, 10.0]];
+        assert_eq!(solve(&costs), vec![Some(1), Some(0)]);
+    }
+
+    #[test]
+    fn square_matrices_match_brute_force() {
+        // A small linear congruential generator keeps the matrices deterministic.
+        let mut state: u64 = 12345;
+        let mut next = || {
+            state = state.wrapping_mul(6364136223846793005).wrapping_add(1442695040888963407);
+            (state >> 33) as f64 / (1u64 << 31) as f64 * 100.0
+        };
+        for size in 1..=5 {
+            for _ in 0..20 {
+                let costs: Vec<Vec<f64>> = (0..size).map(|_| (0..size).map(|_| next()).collect()).collect();
+                let assignment = solve(&costs);
+                assert!(assignment.iter().all(Option::is_some));
+                assert!((total_cost(&costs, &assignment) - brute_force(&costs)).abs() < 1e-9);
+            }
+        }
+    }
+
+    #[test]
+    fn wide_matrix_leaves_columns_unused() {
+        let costs = vec![vec![5.0, 1.0, 9.0], vec![4.0, 2.0, 8.0]];
+        assert_eq!(solve(&costs), vec![Some(1), Some(0)]);
+    }
+
+    #[test]
+    fn tall_matrix_leaves_rows_unassigned() {
+        let costs = vec![vec![9.0, 9.0], vec![1.0, 9.0], vec![9.0, 1.0]];
+        assert_eq!(solve(&costs), vec![None, Some(0), Some(1)]);
+
+        let costs = vec![vec![1.0, 2.0], vec![1.0, 10.0], vec![7.0, 7.0]];
+        assert_eq!(solve(&costs), vec![Some(1), Some(0), None]);
+    }
+
+    #[test]
+    fn forbidden_pairs_are_avoided() {
+        let costs = vec![vec![FORBIDDEN, 5.0], vec![1.0, FORBIDDEN]];
+        assert_eq!(solve(&costs), vec![Some(1), Some(0)]);
+
+        let costs = vec![vec![f64::NAN, 3.0, 1.0], vec![2.0, f64::NEG_INFINITY, 1.0]];
+        assert_eq!(solve(&costs), vec![Some(2), Some(0)]);
+    }
+
+    #[test]
+    fn forbidden_pairs_are_never_returned() {
+        // Both rows can only use column 0, so one of them stays unassigned.
+        let costs = vec![vec![1.0, FORBIDDEN], vec![2.0, FORBIDDEN]];
+        assert_eq!(solve(&costs), vec![Some(0), None]);
+
+        let costs = vec![vec![FORBIDDEN, FORBIDDEN], vec![4.0, 2.0]];
+        assert_eq!(solve(&costs), vec![None, Some(1)]);
+
+        let costs = vec![vec![FORBIDDEN], vec![FORBIDDEN], vec![3.0]];
+        assert_eq!(solve(&costs), vec![None, None, Some(0)]);
+    }
+}
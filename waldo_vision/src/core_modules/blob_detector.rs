@@ -8,9 +8,10 @@
 //
 // Key architectural principles & algorithm steps:
 // 1.  **Heatmap Generation**: It first transforms the `Vec<ChunkStatus>` into a 2D
-//     grid of floating-point "heat" values, using the `luminance_score` from each
-//     `AnomalousEvent`. This preserves the magnitude of the anomaly, unlike a simple
-//     binary approach.
+//     grid of floating-point "heat" values, scoring each `AnomalousEvent` with a
+//     configurable heat function (the `luminance_score` by default, or a blend of the
+//     luminance, color and hue evidence). This preserves the magnitude of the anomaly,
+//     unlike a simple binary approach. The heatmap is handed back to callers as well.
 // 2.  **Peak Finding (Seeding)**: It scans the heatmap to find "local maxima" - chunks
 //     that are hotter than all of their immediate neighbors. These peaks are the
 //     epicenters of motion and become the "seeds" for new blobs. This ensures we
@@ -22,16 +23,153 @@
 // 4.  **Data Aggregation**: Once a blob is fully grown, its high-level properties
 //     (bounding box, center of mass, average anomaly scores) are calculated and
 //     packaged into a `SmartBlob` struct.
-// 5.  **Stateless Utility**: The `BlobDetector` is a stateless utility. Its `find_blobs`
+// 5.  **Alternative Labeling**: As an alternative to peak seeding, a classic two-pass
+//     connected-component labeler can be selected. It treats every chunk above the
+//     growth threshold as foreground, merges provisional labels with a union-find
+//     structure, and supports 4- or 8-connectivity. It is immune to flat plateaus
+//     (which produce several equal peaks) and can join diagonal-only connections.
+// 6.  **Watershed Splitting**: Region growing lets the first peak claim everything it
+//     touches, so two people walking side by side become one blob. The watershed mode
+//     instead floods the heatmap from every peak at once, hottest chunks first. Each
+//     chunk joins the region that reaches it first, so touching objects are split
+//     along the valley of lower heat between their peaks.
+// 7.  **Predictable Motion Support**: An object that has been moving for a while turns
+//     many of its chunks into `PredictableMotion`, which carry no anomaly score. When
+//     enabled, such chunks may join a blob grown from anomalous seeds, contributing
+//     a separately configured weight to its center of mass, but they never seed a
+//     blob on their own.
+// 8.  **Shape Description**: Every blob is measured for aspect ratio, perimeter,
+//     compactness, extent, solidity, orientation and principal axes (from second-order
+//     moments), and its convex hull, so downstream layers can reason about what
+//     kind of object it is rather than only where it is.
+// 9.  **Stateless Utility**: The `BlobDetector` is a stateless utility. Its `find_blobs`
 //     function takes a status map for a single frame and produces a list of blobs
 //     for that same frame. It has no memory of previous frames.
 
-use crate::core_modules::smart_blob::{Point, SmartBlob};
+use crate::core_modules::smart_blob::{BlobShape, Point, SmartBlob};
 use crate::core_modules::smart_chunk::{AnomalyDetails, ChunkStatus};
+use std::cmp::Ordering;
+use std::collections::{BinaryHeap, HashSet};
+use std::f64::consts::PI;
+use std::fmt;
+use std::sync::Arc;
 
 pub mod blob_detector {
     use super::*; // Make structs from parent module available.
 
+    /// Which neighbors count as connected when labeling components.
+    #[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+    pub enum Connectivity {
+        /// Only the four edge-sharing neighbors.
+        Four,
+        /// The four edge-sharing neighbors plus the four diagonals.
+        #[default]
+        Eight,
+    }
+
+    /// The algorithm used to group anomalous chunks into blobs.
+    #[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+    pub enum BlobAlgorithm {
+        /// Seed blobs at local heat maxima and grow them over 4-connected neighbors.
+        #[default]
+        PeakRegionGrowing,
+        /// Two-pass union-find connected-component labeling.
+        ConnectedComponents(Connectivity),
+        /// Marker-based watershed: every peak floods its own region, so touching
+        /// objects are split along the heat valleys between them.
+        Watershed,
+    }
+
+    /// How the heat of an anomalous chunk is derived from its `AnomalyDetails`.
+    #[derive(Clone, Default)]
+    pub enum HeatFunction {
+        /// The `luminance_score` alone.
+        #[default]
+        Luminance,
+        /// A weighted sum of the luminance, color and hue scores.
+        WeightedSum { luminance: f64, color: f64, hue: f64 },
+        /// The largest of the three scores.
+        Max,
+        /// A user-supplied scoring function.
+        Custom(Arc<dyn Fn(&AnomalyDetails) -> f64 + Send + Sync>),
+    }
+
+    impl HeatFunction {
+        /// Wraps a closure as a `Custom` heat function.
+        pub fn custom<F>(function: F) -> Self
+        where
+            F: Fn(&AnomalyDetails) -> f64 + Send + Sync + 'static,
+        {
+            HeatFunction::Custom(Arc::new(function))
+        }
+
+        /// Scores a chunk's anomaly.
+        pub fn heat(&self, details: &AnomalyDetails) -> f64 {
+            match self {
+                HeatFunction::Luminance => details.luminance_score,
+                HeatFunction::WeightedSum { luminance, color, hue } => {
+                    luminance * details.luminance_score + color * details.color_score + hue * details.hue_score
+                }
+                HeatFunction::Max => details.luminance_score.max(details.color_score).max(details.hue_score),
+                HeatFunction::Custom(function) => function(details),
+            }
+        }
+    }
+
+    impl fmt::Debug for HeatFunction {
+        fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+            match self {
+                HeatFunction::Luminance => write!(f, "Luminance"),
+                HeatFunction::WeightedSum { luminance, color, hue } => f
+                    .debug_struct("WeightedSum")
+                    .field("luminance", luminance)
+                    .field("color", color)
+                    .field("hue", hue)
+                    .finish(),
+                HeatFunction::Max => write!(f, "Max"),
+                HeatFunction::Custom(_) => write!(f, "Custom(..)"),
+            }
+        }
+    }
+
+    /// Tunable behavior of the blob detector.
+    #[derive(Debug, Clone)]
+    pub struct BlobDetectorConfig {
+        /// The grouping algorithm.
+        pub algorithm: BlobAlgorithm,
+        /// How anomalous chunks are turned into heat.
+        pub heat_function: HeatFunction,
+        /// The minimum heat a chunk needs to seed a blob.
+        pub peak_threshold: f64,
+        /// The minimum heat a chunk needs to be included in a growing blob.
+        pub growth_threshold: f64,
+        /// If set, `PredictableMotion` chunks join blobs grown from anomalous seeds and
+        /// carry this heat (used for the center of mass and flooding order).
+        pub predictable_motion_weight: Option<f64>,
+    }
+
+    impl Default for BlobDetectorConfig {
+        fn default() -> Self {
+            Self {
+                algorithm: BlobAlgorithm::default(),
+                heat_function: HeatFunction::default(),
+                peak_threshold: DEFAULT_PEAK_THRESHOLD,
+                growth_threshold: DEFAULT_GROWTH_THRESHOLD,
+                predictable_motion_weight: None,
+            }
+        }
+    }
+
+    /// The per-chunk inputs shared by every grouping algorithm.
+    struct HeatGrid {
+        /// Heat of each chunk, used for peak finding, flooding order and center of mass.
+        heat: Vec<Vec<f64>>,
+        /// Whether a chunk may be part of a blob at all.
+        growable: Vec<Vec<bool>>,
+        /// Whether a chunk may seed a blob. Predictable-motion chunks only join blobs.
+        seedable: Vec<Vec<bool>>,
+    }
+
     /// The main function of the spatial analysis layer.
     /// Takes a status map and identifies all coherent blobs of anomalous activity.
     pub fn find_blobs(
@@ -39,17 +177,69 @@ pub mod blob_detector {
         grid_width: u32,
         grid_height: u32,
     ) -> Vec<SmartBlob> {
+        find_blobs_with(status_map, None, grid_width, grid_height, &BlobDetectorConfig::default())
+    }
+
+    /// Identifies blobs using the given detector configuration.
+    /// If a cleaned chunk `mask` is given, it decides which anomalous chunks take part:
+    /// chunks outside it are ignored, and chunks inside it without an anomaly of their
+    /// own (gaps bridged by morphology) join blobs at the minimum growth heat, but never
+    /// seed one.
+    /// Predictable-motion chunks are governed by the config alone, not by the mask.
+    pub fn find_blobs_with(
+        status_map: &[ChunkStatus],
+        mask: Option<&[bool]>,
+        grid_width: u32,
+        grid_height: u32,
+        config: &BlobDetectorConfig,
+    ) -> Vec<SmartBlob> {
+        find_blobs_with_heatmap(status_map, mask, grid_width, grid_height, config).0
+    }
+
+    /// Like `find_blobs_with`, but also returns the heatmap the blobs were found in,
+    /// flattened in the same row-major order as the status map.
+    pub fn find_blobs_with_heatmap(
+        status_map: &[ChunkStatus],
+        mask: Option<&[bool]>,
+        grid_width: u32,
+        grid_height: u32,
+        config: &BlobDetectorConfig,
+    ) -> (Vec<SmartBlob>, Vec<f64>) {
         // --- 1. Heatmap Generation ---
         // Convert the flat Vec<ChunkStatus> into a 2D grid of f64 heat values.
-        // The heat is determined by the luminance_score of an AnomalousEvent.
+        // The heat of an AnomalousEvent is given by the configured heat function.
         // Non-anomalous chunks are given a heat of 0.0.
-        let mut heatmap = vec![vec![0.0; grid_width as usize]; grid_height as usize];
+        let empty_grid = || vec![vec![false; grid_width as usize]; grid_height as usize];
+        let mut grid = HeatGrid {
+            heat: vec![vec![0.0; grid_width as usize]; grid_height as usize],
+            growable: empty_grid(),
+            seedable: empty_grid(),
+        };
         for (i, status) in status_map.iter().enumerate() {
-            if let ChunkStatus::AnomalousEvent(details) = status {
-                let y = i / grid_width as usize;
-                let x = i % grid_width as usize;
-                heatmap[y][x] = details.luminance_score;
-            }
+            let y = i / grid_width as usize;
+            let x = i % grid_width as usize;
+            let in_mask = mask.is_none_or(|mask| mask[i]);
+            let (heat, seedable) = match status {
+                ChunkStatus::AnomalousEvent(details) if in_mask => (config.heat_function.heat(details), true),
+                ChunkStatus::PredictableMotion => {
+                    if let Some(weight) = config.predictable_motion_weight {
+                        grid.heat[y][x] = weight;
+                        grid.growable[y][x] = true;
+                    }
+                    continue;
+                }
+                // Bridged chunks carry no anomaly of their own, so they never seed a blob.
+                _ if in_mask && mask.is_some() => (config.growth_threshold, false),
+                _ => continue,
+            };
+            grid.heat[y][x] = heat;
+            grid.growable[y][x] = heat >= config.growth_threshold;
+            grid.seedable[y][x] = seedable;
+        }
+        let heatmap: Vec<f64> = grid.heat.iter().flatten().copied().collect();
+
+        if let BlobAlgorithm::ConnectedComponents(connectivity) = config.algorithm {
+            return (label_connected_components(&grid, connectivity, status_map, grid_width), heatmap);
         }
 
         // --- 2. Peak Finding ---
@@ -58,9 +248,9 @@ pub mod blob_detector {
         let mut peaks: Vec<Point> = Vec::new();
         for y in 0..grid_height as usize {
             for x in 0..grid_width as usize {
-                let heat = heatmap[y][x];
-                // A chunk must have some heat to be a peak.
-                if heat == 0.0 {
+                let heat = grid.heat[y][x];
+                // A chunk must carry enough anomalous heat of its own to be a peak.
+                if !grid.seedable[y][x] || heat < config.peak_threshold {
                     continue;
                 }
 
@@ -78,7 +268,8 @@ pub mod blob_detector {
 
                         // Check if the neighbor is within the grid boundaries.
                         if ny >= 0 && ny < grid_height as i32 && nx >= 0 && nx < grid_width as i32 {
-                            if heatmap[ny as usize][nx as usize] > heat {
+                            let (ny, nx) = (ny as usize, nx as usize);
+                            if grid.seedable[ny][nx] && grid.heat[ny][nx] > heat {
                                 is_peak = false;
                                 break;
                             }
@@ -98,6 +289,10 @@ pub mod blob_detector {
             }
         }
 
+        if config.algorithm == BlobAlgorithm::Watershed {
+            return (split_by_watershed(&peaks, &grid, status_map, grid_width), heatmap);
+        }
+
         // --- 3. Region Growing & Blob Creation ---
         // For each peak, grow a region and create a blob.
         // A `visited` grid is crucial to ensure we don't process the same chunk twice.
@@ -113,7 +308,7 @@ pub mod blob_detector {
             // Grow a new blob starting from this unvisited peak.
             let new_blob = grow_blob_from_peak(
                 peak,
-                &heatmap,
+                &grid,
                 &mut visited,
                 blob_id_counter,
                 status_map,
@@ -123,18 +318,22 @@ pub mod blob_detector {
             blob_id_counter += 1;
         }
 
-        blobs
+        (blobs, heatmap)
     }
 
-    /// Defines the minimum "heat" a chunk must have to be included in a growing blob.
+    /// The default minimum "heat" a chunk must have to be included in a growing blob.
     /// This acts as the "cold edge" of the blob, preventing it from growing indefinitely
     /// into areas with very low, insignificant anomaly scores.
-    const REGION_GROW_THRESHOLD: f64 = 1.0;
+    pub const DEFAULT_GROWTH_THRESHOLD: f64 = 1.0;
+
+    /// The default minimum heat of a peak. Anomalous chunks score well above it with
+    /// the default heat function, so by default every local maximum seeds a blob.
+    pub const DEFAULT_PEAK_THRESHOLD: f64 = 1.0;
 
     /// Performs a breadth-first search (BFS) to find all connected chunks for a blob.
     fn grow_blob_from_peak(
         peak: Point,
-        heatmap: &[Vec<f64>],
+        grid: &HeatGrid,
         visited: &mut [Vec<bool>],
         blob_id: u64,
         status_map: &[ChunkStatus],
@@ -144,8 +343,8 @@ pub mod blob_detector {
         let mut queue: Vec<Point> = vec![peak];
         visited[peak.y as usize][peak.x as usize] = true;
 
-        let grid_height = heatmap.len() as i32;
-        let grid_width_i32 = heatmap[0].len() as i32;
+        let grid_height = grid.heat.len() as i32;
+        let grid_width_i32 = grid.heat[0].len() as i32;
 
         while let Some(current) = queue.pop() {
             blob_chunks.push(current);
@@ -159,7 +358,7 @@ pub mod blob_detector {
                     let nx_u = nx as usize;
                     let ny_u = ny as usize;
 
-                    if !visited[ny_u][nx_u] && heatmap[ny_u][nx_u] >= REGION_GROW_THRESHOLD {
+                    if !visited[ny_u][nx_u] && grid.growable[ny_u][nx_u] {
                         visited[ny_u][nx_u] = true;
                         queue.push(Point {
                             x: nx_u as u32,
@@ -170,8 +369,240 @@ pub mod blob_detector {
             }
         }
 
+        build_blob(blob_id, blob_chunks, status_map, grid, grid_width)
+    }
+
+    /// A chunk waiting to be flooded. Hotter chunks are flooded first; ties are broken
+    /// by insertion order so the segmentation is deterministic.
+    struct FloodEntry {
+        heat: f64,
+        order: usize,
+        point: Point,
+        label: usize,
+    }
+
+    impl PartialEq for FloodEntry {
+        fn eq(&self, other: &Self) -> bool {
+            self.cmp(other) == Ordering::Equal
+        }
+    }
+
+    impl Eq for FloodEntry {}
+
+    impl PartialOrd for FloodEntry {
+        fn partial_cmp(&self, other: &Self) -> Option<Ordering> {
+            Some(self.cmp(other))
+        }
+    }
+
+    impl Ord for FloodEntry {
+        fn cmp(&self, other: &Self) -> Ordering {
+            self.heat
+                .total_cmp(&other.heat)
+                .then_with(|| other.order.cmp(&self.order))
+        }
+    }
+
+    /// Segments the heatmap with a marker-based watershed (priority flood).
+    /// Adjacent peaks of equal heat form a single marker, so flat plateaus do not
+    /// over-segment an object.
+    fn split_by_watershed(
+        peaks: &[Point],
+        grid: &HeatGrid,
+        status_map: &[ChunkStatus],
+        grid_width: u32,
+    ) -> Vec<SmartBlob> {
+        let heatmap = &grid.heat;
+        let grid_height = heatmap.len() as i32;
+        let grid_width_i32 = grid_width as i32;
+        let mut is_peak = vec![vec![false; grid_width as usize]; heatmap.len()];
+        for peak in peaks {
+            is_peak[peak.y as usize][peak.x as usize] = true;
+        }
+
+        let mut labels: Vec<Vec<Option<usize>>> = vec![vec![None; grid_width as usize]; heatmap.len()];
+        let mut queue = BinaryHeap::new();
+        let mut order = 0;
+        let mut label_count = 0;
+
+        // --- Markers: merge plateaus of equal, adjacent peaks into one label ---
+        for peak in peaks {
+            if labels[peak.y as usize][peak.x as usize].is_some() {
+                continue;
+            }
+            let label = label_count;
+            label_count += 1;
+            let heat = heatmap[peak.y as usize][peak.x as usize];
+            let mut plateau = vec![*peak];
+            labels[peak.y as usize][peak.x as usize] = Some(label);
+
+            while let Some(current) = plateau.pop() {
+                queue.push(FloodEntry { heat, order, point: current, label });
+                order += 1;
+                for dy in -1..=1 {
+                    for dx in -1..=1 {
+                        let nx = current.x as i32 + dx;
+                        let ny = current.y as i32 + dy;
+                        if nx < 0 || nx >= grid_width_i32 || ny < 0 || ny >= grid_height {
+                            continue;
+                        }
+                        let (nx_u, ny_u) = (nx as usize, ny as usize);
+                        if is_peak[ny_u][nx_u] && labels[ny_u][nx_u].is_none() && heatmap[ny_u][nx_u] == heat {
+                            labels[ny_u][nx_u] = Some(label);
+                            plateau.push(Point { x: nx_u as u32, y: ny_u as u32 });
+                        }
+                    }
+                }
+            }
+        }
+
+        // --- Flooding: the hottest frontier chunk claims its unlabeled neighbors ---
+        let mut regions: Vec<Vec<Point>> = vec![Vec::new(); label_count];
+        while let Some(entry) = queue.pop() {
+            regions[entry.label].push(entry.point);
+
+            for (dx, dy) in &[(0, 1), (0, -1), (1, 0), (-1, 0)] {
+                let nx = entry.point.x as i32 + dx;
+                let ny = entry.point.y as i32 + dy;
+                if nx < 0 || nx >= grid_width_i32 || ny < 0 || ny >= grid_height {
+                    continue;
+                }
+                let (nx_u, ny_u) = (nx as usize, ny as usize);
+                let heat = heatmap[ny_u][nx_u];
+                if labels[ny_u][nx_u].is_none() && grid.growable[ny_u][nx_u] {
+                    labels[ny_u][nx_u] = Some(entry.label);
+                    queue.push(FloodEntry {
+                        heat,
+                        order,
+                        point: Point { x: nx_u as u32, y: ny_u as u32 },
+                        label: entry.label,
+                    });
+                    order += 1;
+                }
+            }
+        }
+
+        regions
+            .into_iter()
+            .enumerate()
+            .map(|(blob_id, chunks)| build_blob(blob_id as u64, chunks, status_map, grid, grid_width))
+            .collect()
+    }
+
+    /// A disjoint-set forest over provisional component labels.
+    /// The smallest label of a set is always its root, which keeps output order stable.
+    struct UnionFind {
+        parent: Vec<usize>,
+    }
+
+    impl UnionFind {
+        fn new() -> Self {
+            Self { parent: Vec::new() }
+        }
+
+        fn make_set(&mut self) -> usize {
+            let label = self.parent.len();
+            self.parent.push(label);
+            label
+        }
+
+        fn find(&mut self, mut label: usize) -> usize {
+            while self.parent[label] != label {
+                // Path halving keeps the trees shallow without recursion.
+                self.parent[label] = self.parent[self.parent[label]];
+                label = self.parent[label];
+            }
+            label
+        }
+
+        fn union(&mut self, a: usize, b: usize) {
+            let root_a = self.find(a);
+            let root_b = self.find(b);
+            if root_a != root_b {
+                let (root, child) = if root_a < root_b { (root_a, root_b) } else { (root_b, root_a) };
+                self.parent[child] = root;
+            }
+        }
+    }
+
+    /// Groups all chunks above the growth threshold into connected components using
+    /// the classic two-pass algorithm. Components without a seedable chunk (made only
+    /// of predictable motion) are discarded.
+    fn label_connected_components(
+        grid: &HeatGrid,
+        connectivity: Connectivity,
+        status_map: &[ChunkStatus],
+        grid_width: u32,
+    ) -> Vec<SmartBlob> {
+        // Only neighbors that have already been visited in raster order are inspected.
+        let backward_neighbors: &[(i32, i32)] = match connectivity {
+            Connectivity::Four => &[(-1, 0), (0, -1)],
+            Connectivity::Eight => &[(-1, 0), (-1, -1), (0, -1), (1, -1)],
+        };
+        let grid_height = grid.heat.len();
+        let width = grid_width as usize;
+
+        // --- Pass 1: assign provisional labels and record equivalences ---
+        let mut labels: Vec<Option<usize>> = vec![None; width * grid_height];
+        let mut sets = UnionFind::new();
+        for y in 0..grid_height {
+            for x in 0..width {
+                if !grid.growable[y][x] {
+                    continue;
+                }
+
+                let mut label: Option<usize> = None;
+                for (dx, dy) in backward_neighbors {
+                    let nx = x as i32 + dx;
+                    let ny = y as i32 + dy;
+                    if nx < 0 || ny < 0 || nx >= width as i32 {
+                        continue;
+                    }
+                    if let Some(neighbor) = labels[ny as usize * width + nx as usize] {
+                        match label {
+                            Some(current) => sets.union(current, neighbor),
+                            None => label = Some(neighbor),
+                        }
+                    }
+                }
+                labels[y * width + x] = Some(label.unwrap_or_else(|| sets.make_set()));
+            }
+        }
+
+        // --- Pass 2: resolve each label to its root and collect the chunks ---
+        let mut component_index: Vec<Option<usize>> = vec![None; sets.parent.len()];
+        let mut components: Vec<Vec<Point>> = Vec::new();
+        for (i, label) in labels.iter().enumerate() {
+            if let Some(label) = label {
+                let root = sets.find(*label);
+                let index = *component_index[root].get_or_insert_with(|| {
+                    components.push(Vec::new());
+                    components.len() - 1
+                });
+                components[index].push(Point {
+                    x: (i % width) as u32,
+                    y: (i / width) as u32,
+                });
+            }
+        }
+
+        components
+            .into_iter()
+            .filter(|chunks| chunks.iter().any(|p| grid.seedable[p.y as usize][p.x as usize]))
+            .enumerate()
+            .map(|(blob_id, chunks)| build_blob(blob_id as u64, chunks, status_map, grid, grid_width))
+            .collect()
+    }
+
+    /// Calculates the final properties of a blob from its member chunks.
+    fn build_blob(
+        blob_id: u64,
+        blob_chunks: Vec<Point>,
+        status_map: &[ChunkStatus],
+        grid: &HeatGrid,
+        grid_width: u32,
+    ) -> SmartBlob {
         // --- Data Aggregation ---
-        // Now that we have all the chunks, calculate the final blob properties.
         let mut min_x = u32::MAX;
         let mut min_y = u32::MAX;
         let mut max_x = 0;
@@ -182,6 +613,8 @@ pub mod blob_detector {
         let mut total_heat = 0.0;
         let mut center_x = 0.0;
         let mut center_y = 0.0;
+        let mut anomalous_chunk_count = 0;
+        let mut predictable_chunk_count = 0;
 
         for point in &blob_chunks {
             min_x = min_x.min(point.x);
@@ -190,29 +623,452 @@ pub mod blob_detector {
             max_y = max_y.max(point.y);
 
             let index = (point.y * grid_width + point.x) as usize;
-            if let ChunkStatus::AnomalousEvent(details) = &status_map[index] {
-                let heat = details.luminance_score;
-                total_lum_score += details.luminance_score;
-                total_col_score += details.color_score;
-                total_hue_score += details.hue_score;
-                total_heat += heat;
-                center_x += point.x as f64 * heat;
-                center_y += point.y as f64 * heat;
+            match &status_map[index] {
+                ChunkStatus::AnomalousEvent(details) => {
+                    total_lum_score += details.luminance_score;
+                    total_col_score += details.color_score;
+                    total_hue_score += details.hue_score;
+                    anomalous_chunk_count += 1;
+                }
+                ChunkStatus::PredictableMotion => predictable_chunk_count += 1,
+                _ => {}
             }
+
+            let heat = grid.heat[point.y as usize][point.x as usize];
+            total_heat += heat;
+            center_x += point.x as f64 * heat;
+            center_y += point.y as f64 * heat;
         }
 
         let num_chunks = blob_chunks.len();
+        // Anomaly scores are averaged over the chunks that actually carry them.
+        let num_scored = anomalous_chunk_count.max(1) as f64;
+        // A blob whose chunks carry no heat falls back to its geometric center.
+        if total_heat == 0.0 {
+            total_heat = num_chunks as f64;
+            center_x = blob_chunks.iter().map(|p| p.x as f64).sum();
+            center_y = blob_chunks.iter().map(|p| p.y as f64).sum();
+        }
+        let bounding_box = (Point { x: min_x, y: min_y }, Point { x: max_x, y: max_y });
         SmartBlob {
             id: blob_id,
-            bounding_box: (Point { x: min_x, y: min_y }, Point { x: max_x, y: max_y }),
+            shape: describe_shape(&blob_chunks, bounding_box),
+            appearance: None,
+            foreground: None,
+            bounding_box,
             chunk_coords: blob_chunks,
             size_in_chunks: num_chunks,
+            normalized_size: num_chunks as f64,
+            anomalous_chunk_count,
+            predictable_chunk_count,
             average_anomaly: AnomalyDetails {
-                luminance_score: total_lum_score / num_chunks as f64,
-                color_score: total_col_score / num_chunks as f64,
-                hue_score: total_hue_score / num_chunks as f64,
+                luminance_score: total_lum_score / num_scored,
+                color_score: total_col_score / num_scored,
+                hue_score: total_hue_score / num_scored,
             },
             center_of_mass: (center_x / total_heat, center_y / total_heat),
         }
     }
+
+    /// Variance of a unit square along each axis; added to the moments so that every
+    /// chunk counts as an area, not a point.
+    const UNIT_SQUARE_VARIANCE: f64 = 1.0 / 12.0;
+
+    /// Computes the shape descriptors of a set of chunks.
+    pub fn describe_shape(chunks: &[Point], bounding_box: (Point, Point)) -> BlobShape {
+        let (top_left, bottom_right) = bounding_box;
+        let width = (bottom_right.x - top_left.x + 1) as f64;
+        let height = (bottom_right.y - top_left.y + 1) as f64;
+        let area = chunks.len() as f64;
+
+        // --- Perimeter: every side of a chunk that does not touch another member ---
+        let members: HashSet<Point> = chunks.iter().copied().collect();
+        let mut perimeter = 0.0;
+        for point in chunks {
+            for (dx, dy) in &[(0i64, 1i64), (0, -1), (1, 0), (-1, 0)] {
+                let nx = point.x as i64 + dx;
+                let ny = point.y as i64 + dy;
+                let is_member = nx >= 0 && ny >= 0 && members.contains(&Point { x: nx as u32, y: ny as u32 });
+                if !is_member {
+                    perimeter += 1.0;
+                }
+            }
+        }
+
+        // --- Second-order central moments and the equivalent ellipse ---
+        let mean_x = chunks.iter().map(|p| p.x as f64).sum::<f64>() / area;
+        let mean_y = chunks.iter().map(|p| p.y as f64).sum::<f64>() / area;
+        let mut mu20 = UNIT_SQUARE_VARIANCE;
+        let mut mu02 = UNIT_SQUARE_VARIANCE;
+        let mut mu11 = 0.0;
+        for point in chunks {
+            let dx = point.x as f64 - mean_x;
+            let dy = point.y as f64 - mean_y;
+            mu20 += dx * dx / area;
+            mu02 += dy * dy / area;
+            mu11 += dx * dy / area;
+        }
+        let half_spread = ((mu20 - mu02) / 2.0).hypot(mu11);
+        let major_variance = (mu20 + mu02) / 2.0 + half_spread;
+        let minor_variance = ((mu20 + mu02) / 2.0 - half_spread).max(0.0);
+
+        // --- Solidity: the hull of the chunk centers, grown by half a chunk on every side ---
+        let hull = convex_hull(chunks);
+        let doubled_hull_area: i64 = (0..hull.len())
+            .map(|i| {
+                let (a, b) = (hull[i], hull[(i + 1) % hull.len()]);
+                a.x as i64 * b.y as i64 - b.x as i64 * a.y as i64
+            })
+            .sum();
+        let hull_area = doubled_hull_area.abs() as f64 / 2.0 + width + height - 1.0;
+
+        BlobShape {
+            aspect_ratio: width / height,
+            perimeter,
+            compactness: 4.0 * PI * area / (perimeter * perimeter),
+            extent: area / (width * height),
+            solidity: area / hull_area,
+            orientation: 0.5 * (2.0 * mu11).atan2(mu20 - mu02),
+            major_axis: 4.0 * major_variance.sqrt(),
+            minor_axis: 4.0 * minor_variance.sqrt(),
+            convex_hull: hull,
+        }
+    }
+
+    /// Computes the convex hull of a set of grid points with Andrew's monotone chain.
+    /// Collinear points on the hull's edges are omitted.
+    pub fn convex_hull(points: &[Point]) -> Vec<Point> {
+        let mut sorted: Vec<Point> = points.to_vec();
+        sorted.sort_by_key(|p| (p.x, p.y));
+        sorted.dedup();
+        if sorted.len() < 3 {
+            return sorted;
+        }
+
+        let cross = |o: &Point, a: &Point, b: &Point| {
+            (a.x as i64 - o.x as i64) * (b.y as i64 - o.y as i64)
+                - (a.y as i64 - o.y as i64) * (b.x as i64 - o.x as i64)
+        };
+
+        let mut hull: Vec<Point> = Vec::with_capacity(sorted.len() * 2);
+        // Lower chain, then upper chain; each pops points that would make a clockwise turn.
+        for pass in [sorted.clone(), sorted.iter().rev().copied().collect()] {
+            let chain_start = hull.len();
+            for point in pass {
+                while hull.len() >= chain_start + 2
+                    && cross(&hull[hull.len() - 2], &hull[hull.len() - 1], &point) <= 0
+                {
+                    hull.pop();
+                }
+                hull.push(point);
+            }
+            // The last point of each chain is the first point of the next.
+            hull.pop();
+        }
+        hull
+    }
+
+    #[cfg(test)]
+    mod tests {
+        use super::*;
+
+        const ANOMALY: ChunkStatus = ChunkStatus::AnomalousEvent(AnomalyDetails {
+            luminance_score: 5.0,
+            color_score: 0.0,
+            hue_score: 0.0,
+        });
+
+        /// Builds a status map from rows of text, where `#` is an anomalous chunk.
+        fn status_map(rows: &[&str]) -> (Vec<ChunkStatus>, u32, u32) {
+            let map = rows
+                .iter()
+                .flat_map(|row| row.chars())
+                .map(|c| if c == '#' { ANOMALY } else { ChunkStatus::Stable })
+                .collect();
+            (map, rows[0].len() as u32, rows.len() as u32)
+        }
+
+        fn detect(rows: &[&str], algorithm: BlobAlgorithm) -> Vec<SmartBlob> {
+            let (map, width, height) = status_map(rows);
+            let config = BlobDetectorConfig { algorithm, ..BlobDetectorConfig::default() };
+            find_blobs_with(&map, None, width, height, &config)
+        }
+
+        #[test]
+        fn diagonal_chunks_split_under_four_connectivity() {
+            let rows = ["#....", ".#...", "..#..", "...#.", "....#"];
+            let four = detect(&rows, BlobAlgorithm::ConnectedComponents(Connectivity::Four));
+            assert_eq!(four.len(), 5);
+            assert!(four.iter().all(|blob| blob.size_in_chunks == 1));
+        }
+
+        #[test]
+        fn diagonal_chunks_join_under_eight_connectivity() {
+            let rows = ["#....", ".#...", "..#..", "...#.", "....#"];
+            let eight = detect(&rows, BlobAlgorithm::ConnectedComponents(Connectivity::Eight));
+            assert_eq!(eight.len(), 1);
+            assert_eq!(eight[0].size_in_chunks, 5);
+        }
+
+        #[test]
+        fn labeling_merges_provisional_labels() {
+            // The two arms get different labels in the first pass and meet on the last row.
+            let rows = ["#...#", "#...#", "#####"];
+            let blobs = detect(&rows, BlobAlgorithm::ConnectedComponents(Connectivity::Four));
+            assert_eq!(blobs.len(), 1);
+            assert_eq!(blobs[0].size_in_chunks, 9);
+        }
+
+        #[test]
+        fn flat_plateau_is_not_fragmented_by_labeling() {
+            // Two plateaus of equal heat that only touch at a corner: every chunk is a
+            // peak, and growing over edge neighbors splits the object in two.
+            let rows = ["###...", "###...", "###...", "...###", "...###", "...###"];
+            let grown = detect(&rows, BlobAlgorithm::PeakRegionGrowing);
+            assert_eq!(grown.len(), 2);
+
+            let labeled = detect(&rows, BlobAlgorithm::ConnectedComponents(Connectivity::Eight));
+            assert_eq!(labeled.len(), 1);
+            assert_eq!(labeled[0].size_in_chunks, 18);
+            assert_eq!(labeled[0].bounding_box, (Point { x: 0, y: 0 }, Point { x: 5, y: 5 }));
+        }
+
+        #[test]
+        fn bridged_chunks_join_but_do_not_seed_blobs() {
+            let (map, width, height) = status_map(&["##......"]);
+            let mask = [true, true, true, false, false, true, true, false];
+            for algorithm in [
+                BlobAlgorithm::PeakRegionGrowing,
+                BlobAlgorithm::ConnectedComponents(Connectivity::Four),
+                BlobAlgorithm::Watershed,
+            ] {
+                let config = BlobDetectorConfig { algorithm, ..BlobDetectorConfig::default() };
+                let blobs = find_blobs_with(&map, Some(&mask), width, height, &config);
+                // The bridged chunk joins the anomalies; the run without any is dropped.
+                assert_eq!(blobs.len(), 1, "{algorithm:?}");
+                assert_eq!(blobs[0].size_in_chunks, 3, "{algorithm:?}");
+                assert_eq!(blobs[0].anomalous_chunk_count, 2, "{algorithm:?}");
+            }
+        }
+
+        #[test]
+        fn chunks_below_growth_threshold_are_background() {
+            let (mut map, width, height) = status_map(&["##.##"]);
+            map[1] = ChunkStatus::AnomalousEvent(AnomalyDetails {
+                luminance_score: 0.5,
+                color_score: 0.0,
+                hue_score: 0.0,
+            });
+            let config = BlobDetectorConfig {
+                algorithm: BlobAlgorithm::ConnectedComponents(Connectivity::Eight),
+                ..BlobDetectorConfig::default()
+            };
+            let blobs = find_blobs_with(&map, None, width, height, &config);
+            assert_eq!(blobs.len(), 2);
+            assert_eq!(blobs[0].size_in_chunks, 1);
+            assert_eq!(blobs[1].size_in_chunks, 2);
+        }
+
+        #[test]
+        fn watershed_splits_two_peaks_at_the_saddle() {
+            // One connected row with two hot spots and a cooler saddle between them.
+            let heats = [4.0, 6.0, 4.0, 2.0, 3.0, 7.0, 3.0];
+            let map: Vec<ChunkStatus> = heats
+                .iter()
+                .map(|&luminance_score| {
+                    ChunkStatus::AnomalousEvent(AnomalyDetails { luminance_score, color_score: 0.0, hue_score: 0.0 })
+                })
+                .collect();
+            let find = |algorithm| {
+                let config = BlobDetectorConfig { algorithm, ..BlobDetectorConfig::default() };
+                find_blobs_with(&map, None, heats.len() as u32, 1, &config)
+            };
+
+            let grown = find(BlobAlgorithm::PeakRegionGrowing);
+            assert_eq!(grown.len(), 1);
+            assert_eq!(grown[0].size_in_chunks, 7);
+
+            // The hotter peak floods first, but the saddle is reached from the left
+            // slope before the right one cools down to it.
+            let mut split = find(BlobAlgorithm::Watershed);
+            split.sort_by_key(|blob| blob.bounding_box.0.x);
+            assert_eq!(split.len(), 2);
+            assert_eq!(split[0].size_in_chunks, 4);
+            assert_eq!(split[0].bounding_box, (Point { x: 0, y: 0 }, Point { x: 3, y: 0 }));
+            assert_eq!(split[1].size_in_chunks, 3);
+            assert_eq!(split[1].bounding_box, (Point { x: 4, y: 0 }, Point { x: 6, y: 0 }));
+        }
+
+        #[test]
+        fn heat_functions_score_known_details() {
+            let details = AnomalyDetails { luminance_score: 2.0, color_score: 6.0, hue_score: 4.0 };
+            assert_eq!(HeatFunction::Luminance.heat(&details), 2.0);
+            assert_eq!(HeatFunction::Max.heat(&details), 6.0);
+            let weighted = HeatFunction::WeightedSum { luminance: 1.0, color: 0.5, hue: 0.25 };
+            assert_eq!(weighted.heat(&details), 2.0 + 3.0 + 1.0);
+            let custom = HeatFunction::custom(|details| details.hue_score * details.hue_score);
+            assert_eq!(custom.heat(&details), 16.0);
+        }
+
+        #[test]
+        fn heatmap_holds_the_heat_of_each_chunk() {
+            let scored = |luminance_score, color_score| {
+                ChunkStatus::AnomalousEvent(AnomalyDetails { luminance_score, color_score, hue_score: 0.0 })
+            };
+            let map = vec![
+                scored(3.0, 1.0),
+                scored(1.0, 4.0),
+                ChunkStatus::PredictableMotion,
+                ChunkStatus::Stable,
+                ChunkStatus::Learning,
+                scored(0.5, 0.5),
+            ];
+            let config = BlobDetectorConfig {
+                heat_function: HeatFunction::Max,
+                predictable_motion_weight: Some(0.75),
+                ..BlobDetectorConfig::default()
+            };
+            let (blobs, heatmap) = find_blobs_with_heatmap(&map, None, 6, 1, &config);
+            assert_eq!(heatmap, [3.0, 4.0, 0.75, 0.0, 0.0, 0.5]);
+            // The 0.5 chunk is below the growth threshold and the predictable chunk
+            // joins the blob, so one blob of three chunks peaks at the second chunk.
+            assert_eq!(blobs.len(), 1);
+            assert_eq!(blobs[0].size_in_chunks, 3);
+            assert_eq!(blobs[0].predictable_chunk_count, 1);
+            let center = (0.0 * 3.0 + 1.0 * 4.0 + 2.0 * 0.75) / (3.0 + 4.0 + 0.75);
+            assert!((blobs[0].center_of_mass.0 - center).abs() < 1e-9);
+        }
+
+        #[test]
+        fn thresholds_gate_seeding_and_growth() {
+            let (mut map, width, height) = status_map(&["#####"]);
+            for (status, heat) in map.iter_mut().zip([1.0, 2.0, 4.0, 2.0, 1.0]) {
+                *status = ChunkStatus::AnomalousEvent(AnomalyDetails { luminance_score: heat, color_score: 0.0, hue_score: 0.0 });
+            }
+            let find = |peak_threshold, growth_threshold| {
+                let config = BlobDetectorConfig { peak_threshold, growth_threshold, ..BlobDetectorConfig::default() };
+                find_blobs_with(&map, None, width, height, &config)
+            };
+            assert_eq!(find(1.0, 1.0)[0].size_in_chunks, 5);
+            assert_eq!(find(1.0, 2.0)[0].size_in_chunks, 3);
+            assert_eq!(find(4.0, 4.0)[0].size_in_chunks, 1);
+            assert!(find(5.0, 1.0).is_empty());
+        }
+
+        /// The chunks marked `#` in the given rows, with their bounding box.
+        fn shape_of(rows: &[&str]) -> BlobShape {
+            let chunks: Vec<Point> = rows
+                .iter()
+                .enumerate()
+                .flat_map(|(y, row)| {
+                    row.chars()
+                        .enumerate()
+                        .filter(|(_, c)| *c == '#')
+                        .map(move |(x, _)| Point { x: x as u32, y: y as u32 })
+                })
+                .collect();
+            let top_left = Point {
+                x: chunks.iter().map(|p| p.x).min().unwrap(),
+                y: chunks.iter().map(|p| p.y).min().unwrap(),
+            };
+            let bottom_right = Point {
+                x: chunks.iter().map(|p| p.x).max().unwrap(),
+                y: chunks.iter().map(|p| p.y).max().unwrap(),
+            };
+            describe_shape(&chunks, (top_left, bottom_right))
+        }
+
+        fn assert_close(actual: f64, expected: f64) {
+            assert!((actual - expected).abs() < 1e-9, "{actual} != {expected}");
+        }
+
+        #[test]
+        fn filled_rectangle_shape() {
+            let shape = shape_of(&["####", "####", "####"]);
+            assert_close(shape.aspect_ratio, 4.0 / 3.0);
+            assert_close(shape.perimeter, 14.0);
+            assert_close(shape.compactness, 4.0 * PI * 12.0 / 196.0);
+            assert_close(shape.extent, 1.0);
+            assert_close(shape.solidity, 1.0);
+            // Wider than tall, so the major axis lies along x.
+            assert_close(shape.orientation, 0.0);
+            // A w x h rectangle has variance w^2 / 12 along its width.
+            assert_close(shape.major_axis, 4.0 * (16.0_f64 / 12.0).sqrt());
+            assert_close(shape.minor_axis, 4.0 * (9.0_f64 / 12.0).sqrt());
+            assert_eq!(
+                shape.convex_hull,
+                vec![Point { x: 0, y: 0 }, Point { x: 3, y: 0 }, Point { x: 3, y: 2 }, Point { x: 0, y: 2 }]
+            );
+        }
+
+        #[test]
+        fn tall_rectangle_is_oriented_along_y() {
+            let shape = shape_of(&["##", "##", "##", "##", "##"]);
+            assert_close(shape.aspect_ratio, 0.4);
+            assert_close(shape.solidity, 1.0);
+            assert_close(shape.orientation.abs(), PI / 2.0);
+            assert!(shape.major_axis > shape.minor_axis);
+        }
+
+        #[test]
+        fn l_shape_is_not_solid() {
+            let shape = shape_of(&["#...", "#...", "#...", "####"]);
+            assert_close(shape.extent, 7.0 / 16.0);
+            assert!(shape.solidity < 1.0);
+            assert!(shape.solidity > shape.extent);
+            assert_close(shape.perimeter, 16.0);
+        }
+
+        #[test]
+        fn diagonal_line_is_oriented_at_45_degrees() {
+            let falling = shape_of(&["#.....", ".#....", "..#...", "...#..", "....#.", ".....#"]);
+            // The y axis points down, so a line running down-right has a positive angle.
+            assert_close(falling.orientation, PI / 4.0);
+            assert!(falling.major_axis > 5.0 * falling.minor_axis);
+            assert_eq!(falling.convex_hull, vec![Point { x: 0, y: 0 }, Point { x: 5, y: 5 }]);
+
+            let rising = shape_of(&[".....#", "....#.", "...#..", "..#...", ".#....", "#....."]);
+            assert_close(rising.orientation, -PI / 4.0);
+            assert!(rising.major_axis > 5.0 * rising.minor_axis);
+        }
+
+        #[test]
+        fn ring_perimeter_counts_the_hole() {
+            let ring = shape_of(&["#####", "#...#", "#...#", "#...#", "#####"]);
+            let solid = shape_of(&["#####", "#####", "#####", "#####", "#####"]);
+            assert_close(solid.perimeter, 20.0);
+            assert_close(ring.perimeter, 32.0);
+            assert_close(ring.extent, 16.0 / 25.0);
+            assert_close(ring.solidity, 16.0 / 25.0);
+            assert_eq!(ring.convex_hull, solid.convex_hull);
+        }
+
+        #[test]
+        fn convex_hull_handles_degenerate_input() {
+            assert!(convex_hull(&[]).is_empty());
+
+            let point = Point { x: 2, y: 3 };
+            assert_eq!(convex_hull(&[point, point, point]), vec![point]);
+
+            // Collinear points reduce to the two ends of the segment.
+            let line: Vec<Point> = (0..5).map(|x| Point { x, y: 1 }).rev().collect();
+            assert_eq!(convex_hull(&line), vec![Point { x: 0, y: 1 }, Point { x: 4, y: 1 }]);
+        }
+
+        #[test]
+        fn convex_hull_drops_duplicate_and_edge_points() {
+            let mut points = Vec::new();
+            for y in 0..3 {
+                for x in 0..3 {
+                    points.push(Point { x, y });
+                    points.push(Point { x, y });
+                }
+            }
+            points.reverse();
+            assert_eq!(
+                convex_hull(&points),
+                vec![Point { x: 0, y: 0 }, Point { x: 2, y: 0 }, Point { x: 2, y: 2 }, Point { x: 0, y: 2 }]
+            );
+        }
+    }
 }
@@ -0,0 +1,323 @@
+// THEORY:
+// The `blob_filter` module decides which detected blobs are worth tracking. Raw
+// detection finds every patch of anomalous activity; most deployments only care about
+// some of them (objects of a plausible size and shape, inside an area of interest,
+// fully in view).
+//
+// Key architectural principles:
+// 1.  **Composable Rules**: Each rule is a `BlobFilter`. The pipeline runs a chain of
+//     them in order, and the first rule that objects to a blob drops it. Custom rules
+//     are added by implementing the trait. The standard size rules are ordinary
+//     entries of the default chain, so they can be retuned, reordered or dropped.
+// 2.  **Explained Rejections**: A filter does not just say no, it says why. Every
+//     dropped blob leaves a `BlobRejection` record naming the filter and the reason,
+//     which the pipeline returns as per-frame telemetry for tuning.
+// 3.  **Context, Not State**: Filters are stateless. Frame-level facts a rule may need,
+//     such as the geometry or the recent blob size statistics kept by the pipeline,
+//     are handed in through a `FilterContext`.
+
+use crate::core_modules::geometry::{FrameGeometry, NormalizedBox};
+use crate::core_modules::smart_blob::{Point, SmartBlob};
+use std::fmt;
+use std::sync::Arc;
+
+/// The minimum normalized blob size used by `default_blob_filters`.
+pub const DEFAULT_MIN_BLOB_SIZE: f64 = 2.0;
+
+/// The number of standard deviations below the mean blob size at which
+/// `default_blob_filters` drops a blob as a size outlier.
+pub const DEFAULT_SIZE_OUTLIER_STD_DEVS: f64 = 2.0;
+
+/// Frame-level information available to every filter.
+#[derive(Debug, Clone, Copy)]
+pub struct FilterContext {
+    /// The geometry of the frame being filtered.
+    pub geometry: FrameGeometry,
+    /// The mean and standard deviation of recent normalized blob sizes, once enough
+    /// history has been collected to be meaningful.
+    pub size_statistics: Option<(f64, f64)>,
+}
+
+/// A single rule that may reject a blob.
+pub trait BlobFilter: fmt::Debug + Send + Sync {
+    /// A short name identifying the filter in rejection records.
+    fn name(&self) -> &str;
+
+    /// Returns the reason the blob should be dropped, or `None` to keep it.
+    fn reject(&self, blob: &SmartBlob, context: &FilterContext) -> Option<String>;
+}
+
+/// The record of a blob dropped by a filter.
+#[derive(Debug, Clone, PartialEq)]
+pub struct BlobRejection {
+    /// The per-frame ID of the dropped blob.
+    pub blob_id: u64,
+    /// The grid bounding box of the dropped blob.
+    pub bounding_box: (Point, Point),
+    /// The size of the dropped blob in chunks.
+    pub size_in_chunks: usize,
+    /// The normalized size of the dropped blob.
+    pub normalized_size: f64,
+    /// The name of the filter that dropped it.
+    pub filter: String,
+    /// Why it was dropped.
+    pub reason: String,
+}
+
+/// Runs a blob through a chain of filters. Returns the rejection from the first filter
+/// that objects, or `None` if every filter keeps the blob.
+pub fn apply_chain<'a, I>(filters: I, blob: &SmartBlob, context: &FilterContext) -> Option<BlobRejection>
+where
+    I: IntoIterator<Item = &'a dyn BlobFilter>,
+{
+    filters.into_iter().find_map(|filter| {
+        filter.reject(blob, context).map(|reason| BlobRejection {
+            blob_id: blob.id,
+            bounding_box: blob.bounding_box,
+            size_in_chunks: blob.size_in_chunks,
+            normalized_size: blob.normalized_size,
+            filter: filter.name().to_string(),
+            reason,
+        })
+    })
+}
+
+/// The standard filter chain: a minimum size rule followed by the size outlier rule.
+pub fn default_blob_filters() -> Vec<Arc<dyn BlobFilter>> {
+    vec![
+        Arc::new(MinSizeFilter(DEFAULT_MIN_BLOB_SIZE)),
+        Arc::new(SizeOutlierFilter(DEFAULT_SIZE_OUTLIER_STD_DEVS)),
+    ]
+}
+
+/// Drops blobs whose normalized size is below the given minimum.
+#[derive(Debug, Clone, Copy)]
+pub struct MinSizeFilter(pub f64);
+
+impl BlobFilter for MinSizeFilter {
+    fn name(&self) -> &str {
+        "min_size"
+    }
+
+    fn reject(&self, blob: &SmartBlob, _context: &FilterContext) -> Option<String> {
+        (blob.normalized_size < self.0)
+            .then(|| format!("size {:.2} is below the minimum of {:.2}", blob.normalized_size, self.0))
+    }
+}
+
+/// Drops blobs whose normalized size is above the given maximum.
+#[derive(Debug, Clone, Copy)]
+pub struct MaxSizeFilter(pub f64);
+
+impl BlobFilter for MaxSizeFilter {
+    fn name(&self) -> &str {
+        "max_size"
+    }
+
+    fn reject(&self, blob: &SmartBlob, _context: &FilterContext) -> Option<String> {
+        (blob.normalized_size > self.0)
+            .then(|| format!("size {:.2} is above the maximum of {:.2}", blob.normalized_size, self.0))
+    }
+}
+
+/// Drops blobs more than the given number of standard deviations below the mean of
+/// recent normalized blob sizes. Inactive until the pipeline has collected enough
+/// size history.
+#[derive(Debug, Clone, Copy)]
+pub struct SizeOutlierFilter(pub f64);
+
+impl BlobFilter for SizeOutlierFilter {
+    fn name(&self) -> &str {
+        "size_outlier"
+    }
+
+    fn reject(&self, blob: &SmartBlob, context: &FilterContext) -> Option<String> {
+        let (mean, std_dev) = context.size_statistics?;
+        let threshold = mean - self.0 * std_dev;
+        (blob.normalized_size < threshold)
+            .then(|| format!("size {:.2} is below the recent size threshold of {:.2}", blob.normalized_size, threshold))
+    }
+}
+
+/// Drops blobs whose bounding box aspect ratio (width / height) is outside a range.
+#[derive(Debug, Clone, Copy)]
+pub struct AspectRatioFilter {
+    pub min: f64,
+    pub max: f64,
+}
+
+impl BlobFilter for AspectRatioFilter {
+    fn name(&self) -> &str {
+        "aspect_ratio"
+    }
+
+    fn reject(&self, blob: &SmartBlob, _context: &FilterContext) -> Option<String> {
+        let aspect_ratio = blob.shape.aspect_ratio;
+        (aspect_ratio < self.min || aspect_ratio > self.max).then(|| {
+            format!("aspect ratio {:.2} is outside {:.2}..{:.2}", aspect_ratio, self.min, self.max)
+        })
+    }
+}
+
+/// Whether a `ZoneFilter` keeps blobs inside its zones or outside them.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum ZoneMode {
+    /// Keep only blobs inside at least one zone.
+    #[default]
+    Include,
+    /// Drop blobs inside any zone.
+    Exclude,
+}
+
+/// Filters blobs by whether their center of mass lies in one of a set of zones.
+/// Zones are given in normalized coordinates so they do not depend on the resolution.
+#[derive(Debug, Clone)]
+pub struct ZoneFilter {
+    pub zones: Vec<NormalizedBox>,
+    pub mode: ZoneMode,
+}
+
+impl BlobFilter for ZoneFilter {
+    fn name(&self) -> &str {
+        "zone"
+    }
+
+    fn reject(&self, blob: &SmartBlob, context: &FilterContext) -> Option<String> {
+        let center = blob.normalized_center_of_mass(&context.geometry);
+        let inside = self.zones.iter().any(|zone| {
+            center.x >= zone.min.x && center.x <= zone.max.x && center.y >= zone.min.y && center.y <= zone.max.y
+        });
+        match (self.mode, inside) {
+            (ZoneMode::Include, false) => Some("center is outside every zone".to_string()),
+            (ZoneMode::Exclude, true) => Some("center is inside an excluded zone".to_string()),
+            _ => None,
+        }
+    }
+}
+
+/// Drops blobs whose average luminance anomaly score is below a minimum.
+#[derive(Debug, Clone, Copy)]
+pub struct MinAnomalyScoreFilter(pub f64);
+
+impl BlobFilter for MinAnomalyScoreFilter {
+    fn name(&self) -> &str {
+        "min_anomaly_score"
+    }
+
+    fn reject(&self, blob: &SmartBlob, _context: &FilterContext) -> Option<String> {
+        let score = blob.average_anomaly.luminance_score;
+        (score < self.0).then(|| format!("anomaly score {:.2} is below the minimum of {:.2}", score, self.0))
+    }
+}
+
+/// Drops blobs whose bounding box comes within `margin` chunks of the grid border,
+/// which are usually objects only partly in view.
+#[derive(Debug, Clone, Copy, Default)]
+pub struct EdgeTouchFilter {
+    pub margin: u32,
+}
+
+impl BlobFilter for EdgeTouchFilter {
+    fn name(&self) -> &str {
+        "edge_touch"
+    }
+
+    fn reject(&self, blob: &SmartBlob, context: &FilterContext) -> Option<String> {
+        let (top_left, bottom_right) = blob.bounding_box;
+        let grid_width = context.geometry.grid_width();
+        let grid_height = context.geometry.grid_height();
+        let touches = top_left.x <= self.margin
+            || top_left.y <= self.margin
+            || bottom_right.x + self.margin + 1 >= grid_width
+            || bottom_right.y + self.margin + 1 >= grid_height;
+        touches.then(|| "bounding box touches the frame edge".to_string())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::core_modules::geometry::NormalizedPoint;
+    use crate::core_modules::tracker::tests::{blob_of, square_at, GRID_HEIGHT, GRID_WIDTH};
+
+    fn context(size_statistics: Option<(f64, f64)>) -> FilterContext {
+        FilterContext {
+            geometry: FrameGeometry::new(GRID_WIDTH * 10, GRID_HEIGHT * 10, 10, 10),
+            size_statistics,
+        }
+    }
+
+    fn rejected_by(filter: &dyn BlobFilter, blob: &SmartBlob) -> bool {
+        filter.reject(blob, &context(None)).is_some()
+    }
+
+    #[test]
+    fn chain_reports_the_first_rejection() {
+        // A 1x4 column is too small and too narrow; only the first objection is kept.
+        let blob = blob_of(&[(10, 10, 1, 4)]);
+        let filters: Vec<Arc<dyn BlobFilter>> = vec![
+            Arc::new(MaxSizeFilter(100.0)),
+            Arc::new(MinSizeFilter(10.0)),
+            Arc::new(AspectRatioFilter { min: 0.5, max: 2.0 }),
+        ];
+        let rejection = apply_chain(filters.iter().map(|f| f.as_ref()), &blob, &context(None)).unwrap();
+        assert_eq!(rejection.filter, "min_size");
+        assert_eq!(rejection.reason, "size 4.00 is below the minimum of 10.00");
+        assert_eq!(rejection.blob_id, blob.id);
+        assert_eq!(rejection.bounding_box, blob.bounding_box);
+        assert_eq!(rejection.size_in_chunks, 4);
+
+        let rejection = apply_chain(filters.iter().skip(2).map(|f| f.as_ref()), &blob, &context(None)).unwrap();
+        assert_eq!(rejection.filter, "aspect_ratio");
+
+        let square = square_at(10, 10);
+        assert_eq!(apply_chain(filters.iter().take(1).map(|f| f.as_ref()), &square, &context(None)), None);
+    }
+
+    #[test]
+    fn size_outlier_filter_waits_for_statistics() {
+        let blob = square_at(10, 10);
+        let filter = SizeOutlierFilter(2.0);
+        assert_eq!(filter.reject(&blob, &context(None)), None);
+        // Threshold 10 - 2 * 2 = 6 drops the 4-chunk square, 10 - 2 * 3 = 4 keeps it.
+        assert!(filter.reject(&blob, &context(Some((10.0, 2.0)))).is_some());
+        assert_eq!(filter.reject(&blob, &context(Some((10.0, 3.0)))), None);
+    }
+
+    #[test]
+    fn zone_filter_includes_or_excludes_by_center() {
+        let left_half = NormalizedBox {
+            min: NormalizedPoint { x: 0.0, y: 0.0 },
+            max: NormalizedPoint { x: 0.5, y: 1.0 },
+        };
+        let (left, right) = (square_at(10, 40), square_at(50, 40));
+
+        let include = ZoneFilter { zones: vec![left_half], mode: ZoneMode::Include };
+        assert!(!rejected_by(&include, &left));
+        assert!(rejected_by(&include, &right));
+
+        let exclude = ZoneFilter { zones: vec![left_half], mode: ZoneMode::Exclude };
+        assert!(rejected_by(&exclude, &left));
+        assert!(!rejected_by(&exclude, &right));
+
+        let nowhere = ZoneFilter { zones: Vec::new(), mode: ZoneMode::Include };
+        assert!(rejected_by(&nowhere, &left));
+    }
+
+    #[test]
+    fn edge_touch_filter_checks_every_border() {
+        let filter = EdgeTouchFilter { margin: 0 };
+        assert!(rejected_by(&filter, &square_at(0, 40)));
+        assert!(rejected_by(&filter, &square_at(30, 0)));
+        assert!(rejected_by(&filter, &square_at(GRID_WIDTH - 2, 40)));
+        assert!(rejected_by(&filter, &square_at(30, GRID_HEIGHT - 2)));
+        assert!(!rejected_by(&filter, &square_at(1, 1)));
+        assert!(!rejected_by(&filter, &square_at(GRID_WIDTH - 3, GRID_HEIGHT - 3)));
+
+        let filter = EdgeTouchFilter { margin: 2 };
+        assert!(rejected_by(&filter, &square_at(2, 40)));
+        assert!(rejected_by(&filter, &square_at(GRID_WIDTH - 4, 40)));
+        assert!(!rejected_by(&filter, &square_at(3, 40)));
+        assert!(!rejected_by(&filter, &square_at(GRID_WIDTH - 5, 40)));
+    }
+}
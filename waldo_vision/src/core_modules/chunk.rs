@@ -20,20 +20,22 @@
 // has changed significantly over time.
 
 pub mod chunk {
-    use crate::core_modules::pixel::pixel::Pixel;
+    use crate::core_modules::pixel::pixel::{BitDepth, LuminanceModel, Pixel, PixelData};
 
     /// A "dumb" data container representing a rectangular block of pixels.
-    pub struct Chunk {
+    /// It is generic over the pixel container so that 8-bit and 16-bit sources
+    /// share the same summarization logic.
+    pub struct Chunk<P: PixelData = Pixel> {
         /// The width of the chunk in pixels.
         pub width: u32,
         /// The height of the chunk in pixels.
         pub height: u32,
-        /// A flattened vector containing all the `Pixel` data within this chunk.
-        pub pixels: Vec<Pixel>,
+        /// A flattened vector containing all the pixel data within this chunk.
+        pub pixels: Vec<P>,
     }
 
-    impl Chunk {
-        pub fn new(width: u32, height: u32, pixels: Vec<Pixel>) -> Self {
+    impl<P: PixelData> Chunk<P> {
+        pub fn new(width: u32, height: u32, pixels: Vec<P>) -> Self {
             // In a real-world scenario, you might add a check here
             // to ensure pixels.len() == (width * height) as usize
             Self {
@@ -45,31 +47,60 @@ pub mod chunk {
 
         /// Calculates the average pixel value for the entire chunk.
         /// This is the core operation for summarizing the chunk's state.
-        pub fn average_pixel(&self) -> Pixel {
+        pub fn average_pixel(&self) -> P {
             let num_pixels = self.pixels.len();
             if num_pixels == 0 {
-                return Pixel::default(); // Return a default pixel if the chunk is empty
+                return P::default(); // Return a default pixel if the chunk is empty
             }
 
-            // Use u32 for sums to prevent overflow when adding many u8 values.
-            let mut sum_r: u32 = 0;
-            let mut sum_g: u32 = 0;
-            let mut sum_b: u32 = 0;
-            let mut sum_a: u32 = 0;
-
+            // Use u64 for sums to prevent overflow when adding many 16-bit values.
+            let mut sums = [0u64; 4];
             for pixel in &self.pixels {
-                sum_r += pixel.red as u32;
-                sum_g += pixel.green as u32;
-                sum_b += pixel.blue as u32;
-                sum_a += pixel.alpha as u32;
+                for (sum, channel) in sums.iter_mut().zip(pixel.channels()) {
+                    *sum += channel as u64;
+                }
             }
 
-            Pixel {
-                red: (sum_r / num_pixels as u32) as u8,
-                green: (sum_g / num_pixels as u32) as u8,
-                blue: (sum_b / num_pixels as u32) as u8,
-                alpha: (sum_a / num_pixels as u32) as u8,
+            P::from_channels(sums.map(|sum| (sum / num_pixels as u64) as u32))
+        }
+
+        /// Calculates the average pixel value using the given luminance model, for
+        /// data of the given bit depth. In linear-light mode the color channels are
+        /// averaged in linear light and re-encoded, so bright and dark pixels contribute
+        /// in proportion to their energy.
+        pub fn average_pixel_at_depth(&self, model: &LuminanceModel, depth: BitDepth) -> P {
+            if !model.linear_light {
+                return self.average_pixel();
             }
+            let num_pixels = self.pixels.len();
+            if num_pixels == 0 {
+                return P::default();
+            }
+
+            let mut sums = [0.0; 3];
+            let mut sum_a: u64 = 0;
+            for pixel in &self.pixels {
+                let [red, green, blue, alpha] = pixel.channels();
+                sums[0] += model.channel_value_at(red, depth);
+                sums[1] += model.channel_value_at(green, depth);
+                sums[2] += model.channel_value_at(blue, depth);
+                sum_a += alpha as u64;
+            }
+
+            let count = num_pixels as f64;
+            P::from_channels([
+                model.encode_at(sums[0] / count, depth),
+                model.encode_at(sums[1] / count, depth),
+                model.encode_at(sums[2] / count, depth),
+                (sum_a / num_pixels as u64) as u32,
+            ])
+        }
+    }
+
+    impl Chunk<Pixel> {
+        /// Calculates the average pixel value of 8-bit data using the given luminance model.
+        pub fn average_pixel_with(&self, model: &LuminanceModel) -> Pixel {
+            self.average_pixel_at_depth(model, BitDepth::Eight)
         }
     }
 }
@@ -0,0 +1,301 @@
+// THEORY:
+// The `FrameDecoder` sits in front of the `GridManager` for sources that deliver
+// compressed frames, such as MJPEG IP cameras and JPEG/PNG snapshot endpoints. Its job
+// is to turn encoded bytes into the flat RGBA buffer that the grid already understands.
+//
+// Key architectural principles:
+// 1.  **Buffer Reuse**: A camera delivers frames of the same size over and over. The
+//     decoder owns its output buffers and reuses them between frames, so steady-state
+//     decoding performs no per-frame allocations for the RGBA data.
+// 2.  **Decode Only What Is Needed**: The temporal layer only ever looks at chunk
+//     averages. For JPEG, the DCT can be evaluated at 1/2, 1/4 or 1/8 scale, which
+//     yields those averages almost for free and skips most of the decoding work.
+// 3.  **Format Sniffing**: The format is detected from the leading magic bytes, so a
+//     caller can feed whatever the camera sends without tracking its content type.
+
+use image::codecs::jpeg::JpegDecoder;
+use image::codecs::png::PngDecoder;
+use image::{ColorType, DynamicImage, ImageDecoder};
+use std::fmt;
+use std::io::Cursor;
+
+const JPEG_MAGIC: &[u8] = &[0xFF, 0xD8];
+const PNG_MAGIC: &[u8] = &[0x89, b'P', b'N', b'G', 0x0D, 0x0A, 0x1A, 0x0A];
+
+/// The compressed formats the decoder understands.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum EncodedFormat {
+    /// A baseline or progressive JPEG, including individual MJPEG frames.
+    Jpeg,
+    Png,
+}
+
+impl EncodedFormat {
+    /// Detects the format from the leading magic bytes of an encoded frame.
+    pub fn sniff(bytes: &[u8]) -> Option<Self> {
+        if bytes.starts_with(JPEG_MAGIC) {
+            Some(EncodedFormat::Jpeg)
+        } else if bytes.starts_with(PNG_MAGIC) {
+            Some(EncodedFormat::Png)
+        } else {
+            None
+        }
+    }
+}
+
+/// The resolution at which JPEG frames are decoded. Reduced scales are computed
+/// directly in the DCT domain and are much cheaper than a full decode.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum JpegScale {
+    #[default]
+    Full,
+    Half,
+    Quarter,
+    Eighth,
+}
+
+impl JpegScale {
+    /// The denominator of the scale factor (1, 2, 4 or 8).
+    pub fn denominator(&self) -> u32 {
+        match self {
+            JpegScale::Full => 1,
+            JpegScale::Half => 2,
+            JpegScale::Quarter => 4,
+            JpegScale::Eighth => 8,
+        }
+    }
+}
+
+/// Describes why an encoded frame could not be decoded.
+#[derive(Debug, Clone, PartialEq)]
+pub enum DecodeError {
+    /// The bytes are neither a JPEG nor a PNG.
+    UnrecognizedFormat,
+    /// The frame uses a pixel format the decoder cannot convert to RGBA.
+    UnsupportedPixelFormat(String),
+    /// The underlying codec rejected the data.
+    Codec(String),
+}
+
+impl fmt::Display for DecodeError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            DecodeError::UnrecognizedFormat => write!(f, "encoded frame is neither JPEG nor PNG"),
+            DecodeError::UnsupportedPixelFormat(format) => write!(f, "unsupported pixel format: {}", format),
+            DecodeError::Codec(message) => write!(f, "failed to decode frame: {}", message),
+        }
+    }
+}
+
+impl std::error::Error for DecodeError {}
+
+/// Decodes compressed frames into a reusable RGBA buffer.
+#[derive(Default)]
+pub struct FrameDecoder {
+    /// The decoded frame in the flat RGBA layout expected by the `GridManager`.
+    rgba_buffer: Vec<u8>,
+    /// Scratch space for the codec's native output before RGBA expansion.
+    native_buffer: Vec<u8>,
+}
+
+impl FrameDecoder {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Decodes an encoded frame and returns its dimensions together with the RGBA data.
+    /// `jpeg_scale` is ignored for PNG frames.
+    pub fn decode(&mut self, bytes: &[u8], jpeg_scale: JpegScale) -> Result<(u32, u32, &[u8]), DecodeError> {
+        let (width, height) = match EncodedFormat::sniff(bytes) {
+            Some(EncodedFormat::Jpeg) if jpeg_scale != JpegScale::Full => {
+                self.decode_scaled_jpeg(bytes, jpeg_scale)?
+            }
+            Some(EncodedFormat::Jpeg) => {
+                let decoder = JpegDecoder::new(Cursor::new(bytes)).map_err(codec_error)?;
+                self.decode_with(decoder)?
+            }
+            Some(EncodedFormat::Png) => {
+                let decoder = PngDecoder::new(Cursor::new(bytes)).map_err(codec_error)?;
+                self.decode_with(decoder)?
+            }
+            None => return Err(DecodeError::UnrecognizedFormat),
+        };
+        Ok((width, height, &self.rgba_buffer))
+    }
+
+    /// Decodes through an `image` crate decoder. 8-bit layouts are expanded in place;
+    /// anything else is converted through a `DynamicImage`.
+    fn decode_with<D: ImageDecoder>(&mut self, decoder: D) -> Result<(u32, u32), DecodeError> {
+        let (width, height) = decoder.dimensions();
+        let color_type = decoder.color_type();
+        let channels = match color_type {
+            ColorType::L8 => 1,
+            ColorType::La8 => 2,
+            ColorType::Rgb8 => 3,
+            ColorType::Rgba8 => 4,
+            _ => {
+                let image = DynamicImage::from_decoder(decoder).map_err(codec_error)?;
+                self.rgba_buffer.clear();
+                self.rgba_buffer.extend_from_slice(image.to_rgba8().as_raw());
+                return Ok((width, height));
+            }
+        };
+
+        self.native_buffer.resize(decoder.total_bytes() as usize, 0);
+        decoder.read_image(&mut self.native_buffer).map_err(codec_error)?;
+        expand_to_rgba(&self.native_buffer, channels, &mut self.rgba_buffer);
+        Ok((width, height))
+    }
+
+    /// Decodes a JPEG at reduced resolution by evaluating a smaller inverse DCT.
+    ///
+    /// Unlike the full-resolution path, this one allocates once per frame:
+    /// `jpeg_decoder` (0.3) only returns its output as a new `Vec` and has no way to
+    /// decode into a caller's buffer. The RGBA output buffer is still reused.
+    fn decode_scaled_jpeg(&mut self, bytes: &[u8], scale: JpegScale) -> Result<(u32, u32), DecodeError> {
+        let mut decoder = jpeg_decoder::Decoder::new(Cursor::new(bytes));
+        decoder.read_info().map_err(codec_error)?;
+        let info = decoder.info().ok_or_else(|| DecodeError::Codec("missing JPEG header".to_string()))?;
+
+        let denominator = scale.denominator() as u16;
+        let (width, height) = decoder
+            .scale(info.width.div_ceil(denominator), info.height.div_ceil(denominator))
+            .map_err(codec_error)?;
+        let pixels = decoder.decode().map_err(codec_error)?;
+
+        let channels = match info.pixel_format {
+            jpeg_decoder::PixelFormat::L8 => 1,
+            jpeg_decoder::PixelFormat::RGB24 => 3,
+            jpeg_decoder::PixelFormat::CMYK32 => {
+                cmyk_to_rgba(&pixels, &mut self.rgba_buffer);
+                return Ok((width as u32, height as u32));
+            }
+            other => return Err(DecodeError::UnsupportedPixelFormat(format!("{:?}", other))),
+        };
+        expand_to_rgba(&pixels, channels, &mut self.rgba_buffer);
+        Ok((width as u32, height as u32))
+    }
+}
+
+/// Expands 8-bit gray, gray-alpha, RGB or RGBA samples into the RGBA output buffer.
+fn expand_to_rgba(samples: &[u8], channels: usize, rgba: &mut Vec<u8>) {
+    rgba.clear();
+    rgba.reserve(samples.len() / channels * 4);
+    for pixel in samples.chunks_exact(channels) {
+        match channels {
+            1 => rgba.extend_from_slice(&[pixel[0], pixel[0], pixel[0], u8::MAX]),
+            2 => rgba.extend_from_slice(&[pixel[0], pixel[0], pixel[0], pixel[1]]),
+            3 => rgba.extend_from_slice(&[pixel[0], pixel[1], pixel[2], u8::MAX]),
+            _ => rgba.extend_from_slice(pixel),
+        }
+    }
+}
+
+/// Converts `jpeg_decoder` CMYK output into the RGBA output buffer. `jpeg_decoder`
+/// returns the complement of the stored (Adobe-inverted) samples, so each color is
+/// the stored ink times the stored black, exactly as the full-resolution decoder
+/// computes it. This keeps both paths in agreement on CMYK frames.
+fn cmyk_to_rgba(samples: &[u8], rgba: &mut Vec<u8>) {
+    let blend = |ink: u8, black: u8| {
+        let product = (255 - ink as i32) * (255 - black as i32) + 128;
+        ((product + (product >> 8)) >> 8) as u8
+    };
+    rgba.clear();
+    rgba.reserve(samples.len());
+    for pixel in samples.chunks_exact(4) {
+        let black = pixel[3];
+        rgba.extend_from_slice(&[blend(pixel[0], black), blend(pixel[1], black), blend(pixel[2], black), u8::MAX]);
+    }
+}
+
+fn codec_error<E: fmt::Display>(error: E) -> DecodeError {
+    DecodeError::Codec(error.to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use image::{ImageFormat, Rgb, RgbImage, RgbaImage};
+
+    /// A 16x16 CMYK JPEG with one flat color per 8x8 block: orange, mid-gray,
+    /// green and dark gray, in stored samples (255, 128, 64, 255), (255, 255, 255, 128),
+    /// (64, 255, 128, 255) and (128, 128, 128, 128).
+    const CMYK_JPEG: &[u8] = include_bytes!("../../tests/fixtures/cmyk.jpg");
+
+    fn encode(image: DynamicImage, format: ImageFormat) -> Vec<u8> {
+        let mut bytes = Cursor::new(Vec::new());
+        image.write_to(&mut bytes, format).unwrap();
+        bytes.into_inner()
+    }
+
+    /// A 32x16 JPEG whose left half is red and right half is blue.
+    fn two_color_jpeg() -> Vec<u8> {
+        let image = RgbImage::from_fn(32, 16, |x, _| if x < 16 { Rgb([200, 40, 40]) } else { Rgb([40, 40, 200]) });
+        encode(DynamicImage::ImageRgb8(image), ImageFormat::Jpeg)
+    }
+
+    fn rgb_at(rgba: &[u8], width: u32, x: u32, y: u32) -> [u8; 3] {
+        let index = ((y * width + x) * 4) as usize;
+        [rgba[index], rgba[index + 1], rgba[index + 2]]
+    }
+
+    fn assert_near(actual: [u8; 3], expected: [u8; 3]) {
+        let close = actual.iter().zip(expected).all(|(a, e)| (*a as i32 - e as i32).abs() <= 3);
+        assert!(close, "{actual:?} != {expected:?}");
+    }
+
+    #[test]
+    fn png_decodes_losslessly() {
+        let image = RgbaImage::from_fn(5, 3, |x, y| image::Rgba([x as u8 * 50, y as u8 * 80, 7, 200]));
+        let bytes = encode(DynamicImage::ImageRgba8(image.clone()), ImageFormat::Png);
+        let mut decoder = FrameDecoder::new();
+        // The JPEG scale does not apply to PNG.
+        let (width, height, rgba) = decoder.decode(&bytes, JpegScale::Quarter).unwrap();
+        assert_eq!((width, height), (5, 3));
+        assert_eq!(rgba, image.as_raw().as_slice());
+    }
+
+    #[test]
+    fn jpeg_decodes_at_every_scale() {
+        let bytes = two_color_jpeg();
+        let mut decoder = FrameDecoder::new();
+        for (scale, expected) in [
+            (JpegScale::Full, (32, 16)),
+            (JpegScale::Half, (16, 8)),
+            (JpegScale::Quarter, (8, 4)),
+            (JpegScale::Eighth, (4, 2)),
+        ] {
+            let (width, height, rgba) = decoder.decode(&bytes, scale).unwrap();
+            assert_eq!((width, height), expected, "{scale:?}");
+            assert_eq!(rgba.len(), (width * height * 4) as usize, "{scale:?}");
+            assert_near(rgb_at(rgba, width, 0, 0), [200, 40, 40]);
+            assert_near(rgb_at(rgba, width, width - 1, height - 1), [40, 40, 200]);
+        }
+    }
+
+    #[test]
+    fn cmyk_jpeg_decodes_alike_at_full_and_reduced_scale() {
+        let mut decoder = FrameDecoder::new();
+        let (width, height, full) = decoder.decode(CMYK_JPEG, JpegScale::Full).unwrap();
+        assert_eq!((width, height), (16, 16));
+        let full: Vec<[u8; 3]> = [(4, 4), (12, 4), (4, 12), (12, 12)].iter().map(|&(x, y)| rgb_at(full, 16, x, y)).collect();
+        assert_near(full[0], [255, 128, 64]);
+        assert_near(full[1], [128, 128, 128]);
+        assert_near(full[2], [64, 255, 128]);
+        assert_near(full[3], [64, 64, 64]);
+
+        let (width, height, eighth) = decoder.decode(CMYK_JPEG, JpegScale::Eighth).unwrap();
+        assert_eq!((width, height), (2, 2));
+        for (index, (x, y)) in [(0, 0), (1, 0), (0, 1), (1, 1)].into_iter().enumerate() {
+            assert_near(rgb_at(eighth, 2, x, y), full[index]);
+        }
+    }
+
+    #[test]
+    fn unknown_bytes_are_rejected() {
+        let mut decoder = FrameDecoder::new();
+        assert_eq!(decoder.decode(b"GIF89a", JpegScale::Full).err(), Some(DecodeError::UnrecognizedFormat));
+        let truncated = &two_color_jpeg()[..20];
+        assert!(matches!(decoder.decode(truncated, JpegScale::Half), Err(DecodeError::Codec(_))));
+    }
+}
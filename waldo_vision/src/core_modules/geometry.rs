@@ -0,0 +1,280 @@
+// THEORY:
+// The `geometry` module names the coordinate spaces the vision system works in, so
+// that consumers no longer have to multiply grid coordinates by the chunk size by
+// hand (and get the half-chunk offset wrong).
+//
+// Key architectural principles:
+// 1.  **Three Typed Spaces**: Grid coordinates count chunks and are what the analysis
+//     layers use internally (`Point` for whole chunks, `GridPoint` for positions such
+//     as a center of mass). Pixel coordinates are continuous image positions, with
+//     the top-left corner of the image at (0, 0). Normalized coordinates divide pixel
+//     coordinates by the image size, so the image spans 0..1 on both axes.
+// 2.  **Bound to the Pipeline Geometry**: Conversions go through a `FrameGeometry`
+//     built from the `PipelineConfig`, the single source of the image and chunk sizes.
+// 3.  **Chunk Semantics**: A grid position `x` refers to the center of chunk `x`, so it
+//     maps to pixel `(x + 0.5) * chunk_width`. A grid box of whole chunks covers their
+//     full pixel area, from the left edge of its first chunk to the right edge of its last.
+// 4.  **Grid Fields, Converted on Demand**: Output structs keep a single stored form,
+//     in grid units (`SmartBlob::center_of_mass` and `bounding_box`,
+//     `TrackedBlob::position_history`, `Moment::path`), because the tracker's motion and
+//     gating math runs in that space. Their pixel and normalized forms are methods taking
+//     the `FrameGeometry` that every `FrameAnalysis` carries, rather than extra fields
+//     that would have to be kept in sync.
+
+use crate::core_modules::smart_blob::Point;
+
+/// A length given independently of the chunk size, for settings such as tracking gates.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub enum Distance {
+    /// A length in image pixels.
+    Pixels(f64),
+    /// A fraction of the image diagonal, so the setting does not depend on the resolution.
+    Normalized(f64),
+}
+
+/// A position in grid (chunk) units, such as a blob's center of mass.
+#[derive(Debug, Clone, Copy, PartialEq, Default)]
+pub struct GridPoint {
+    pub x: f64,
+    pub y: f64,
+}
+
+/// A position in image pixels.
+#[derive(Debug, Clone, Copy, PartialEq, Default)]
+pub struct PixelPoint {
+    pub x: f64,
+    pub y: f64,
+}
+
+/// A position relative to the image size, 0..1 on both axes.
+#[derive(Debug, Clone, Copy, PartialEq, Default)]
+pub struct NormalizedPoint {
+    pub x: f64,
+    pub y: f64,
+}
+
+/// An axis-aligned box in image pixels, from its top-left to its bottom-right corner.
+#[derive(Debug, Clone, Copy, PartialEq, Default)]
+pub struct PixelBox {
+    pub min: PixelPoint,
+    pub max: PixelPoint,
+}
+
+/// An axis-aligned box relative to the image size, from its top-left to its bottom-right corner.
+#[derive(Debug, Clone, Copy, PartialEq, Default)]
+pub struct NormalizedBox {
+    pub min: NormalizedPoint,
+    pub max: NormalizedPoint,
+}
+
+impl From<(f64, f64)> for GridPoint {
+    fn from((x, y): (f64, f64)) -> Self {
+        Self { x, y }
+    }
+}
+
+impl From<Point> for GridPoint {
+    fn from(point: Point) -> Self {
+        Self {
+            x: point.x as f64,
+            y: point.y as f64,
+        }
+    }
+}
+
+impl PixelBox {
+    pub fn width(&self) -> f64 {
+        self.max.x - self.min.x
+    }
+
+    pub fn height(&self) -> f64 {
+        self.max.y - self.min.y
+    }
+}
+
+impl NormalizedBox {
+    pub fn width(&self) -> f64 {
+        self.max.x - self.min.x
+    }
+
+    pub fn height(&self) -> f64 {
+        self.max.y - self.min.y
+    }
+}
+
+/// The image and chunk sizes that tie the three coordinate spaces together.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct FrameGeometry {
+    pub image_width: u32,
+    pub image_height: u32,
+    pub chunk_width: u32,
+    pub chunk_height: u32,
+}
+
+impl FrameGeometry {
+    pub fn new(image_width: u32, image_height: u32, chunk_width: u32, chunk_height: u32) -> Self {
+        Self {
+            image_width,
+            image_height,
+            chunk_width,
+            chunk_height,
+        }
+    }
+
+    /// The width of the grid in chunks.
+    pub fn grid_width(&self) -> u32 {
+        self.image_width / self.chunk_width
+    }
+
+    /// The height of the grid in chunks.
+    pub fn grid_height(&self) -> u32 {
+        self.image_height / self.chunk_height
+    }
+
+    /// A distance in grid units. Chunks that are not square are measured by the mean
+    /// of their width and height.
+    pub fn distance_to_grid(&self, distance: Distance) -> f64 {
+        let pixels = match distance {
+            Distance::Pixels(pixels) => pixels,
+            Distance::Normalized(fraction) => {
+                fraction * (self.image_width as f64).hypot(self.image_height as f64)
+            }
+        };
+        pixels / ((self.chunk_width + self.chunk_height) as f64 / 2.0)
+    }
+
+    /// Maps a grid position to the pixel position it refers to (a chunk's center).
+    pub fn grid_to_pixel(&self, point: impl Into<GridPoint>) -> PixelPoint {
+        let point = point.into();
+        PixelPoint {
+            x: (point.x + 0.5) * self.chunk_width as f64,
+            y: (point.y + 0.5) * self.chunk_height as f64,
+        }
+    }
+
+    /// The inverse of `grid_to_pixel`.
+    pub fn pixel_to_grid(&self, point: PixelPoint) -> GridPoint {
+        GridPoint {
+            x: point.x / self.chunk_width as f64 - 0.5,
+            y: point.y / self.chunk_height as f64 - 0.5,
+        }
+    }
+
+    pub fn pixel_to_normalized(&self, point: PixelPoint) -> NormalizedPoint {
+        NormalizedPoint {
+            x: point.x / self.image_width as f64,
+            y: point.y / self.image_height as f64,
+        }
+    }
+
+    pub fn normalized_to_pixel(&self, point: NormalizedPoint) -> PixelPoint {
+        PixelPoint {
+            x: point.x * self.image_width as f64,
+            y: point.y * self.image_height as f64,
+        }
+    }
+
+    pub fn grid_to_normalized(&self, point: impl Into<GridPoint>) -> NormalizedPoint {
+        self.pixel_to_normalized(self.grid_to_pixel(point))
+    }
+
+    pub fn normalized_to_grid(&self, point: NormalizedPoint) -> GridPoint {
+        self.pixel_to_grid(self.normalized_to_pixel(point))
+    }
+
+    /// The pixel area covered by a single chunk.
+    pub fn chunk_to_pixel_box(&self, chunk: Point) -> PixelBox {
+        self.grid_box_to_pixel((chunk, chunk))
+    }
+
+    /// The pixel area covered by an inclusive box of chunks, such as a blob's bounding box.
+    pub fn grid_box_to_pixel(&self, (top_left, bottom_right): (Point, Point)) -> PixelBox {
+        PixelBox {
+            min: PixelPoint {
+                x: (top_left.x * self.chunk_width) as f64,
+                y: (top_left.y * self.chunk_height) as f64,
+            },
+            max: PixelPoint {
+                x: ((bottom_right.x + 1) * self.chunk_width) as f64,
+                y: ((bottom_right.y + 1) * self.chunk_height) as f64,
+            },
+        }
+    }
+
+    pub fn pixel_box_to_normalized(&self, pixel_box: PixelBox) -> NormalizedBox {
+        NormalizedBox {
+            min: self.pixel_to_normalized(pixel_box.min),
+            max: self.pixel_to_normalized(pixel_box.max),
+        }
+    }
+
+    pub fn grid_box_to_normalized(&self, grid_box: (Point, Point)) -> NormalizedBox {
+        self.pixel_box_to_normalized(self.grid_box_to_pixel(grid_box))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// A 100x60 image of 10x6 chunks, so both axes span 10 chunks but scale differently.
+    fn geometry() -> FrameGeometry {
+        FrameGeometry::new(100, 60, 10, 6)
+    }
+
+    fn assert_close(actual: (f64, f64), expected: (f64, f64)) {
+        assert!(
+            (actual.0 - expected.0).abs() < 1e-9 && (actual.1 - expected.1).abs() < 1e-9,
+            "{actual:?} != {expected:?}"
+        );
+    }
+
+    #[test]
+    fn grid_positions_map_to_chunk_centers() {
+        let geometry = geometry();
+        let pixel = geometry.grid_to_pixel(Point { x: 0, y: 0 });
+        assert_eq!(pixel, PixelPoint { x: 5.0, y: 3.0 });
+        let pixel = geometry.grid_to_pixel((9.0, 9.0));
+        assert_eq!(pixel, PixelPoint { x: 95.0, y: 57.0 });
+        let normalized = geometry.grid_to_normalized((4.5, 4.5));
+        assert_close((normalized.x, normalized.y), (0.5, 0.5));
+    }
+
+    #[test]
+    fn conversions_round_trip() {
+        let geometry = geometry();
+        for grid in [(0.0, 0.0), (2.25, 7.5), (9.0, 0.4), (-0.5, 9.5)] {
+            let pixel = geometry.grid_to_pixel(grid);
+            let back = geometry.pixel_to_grid(pixel);
+            assert_close((back.x, back.y), grid);
+
+            let normalized = geometry.grid_to_normalized(grid);
+            let back = geometry.normalized_to_grid(normalized);
+            assert_close((back.x, back.y), grid);
+
+            let back = geometry.normalized_to_pixel(geometry.pixel_to_normalized(pixel));
+            assert_close((back.x, back.y), (pixel.x, pixel.y));
+        }
+    }
+
+    #[test]
+    fn grid_boxes_cover_whole_chunks() {
+        let geometry = geometry();
+        let chunk = geometry.chunk_to_pixel_box(Point { x: 2, y: 3 });
+        assert_eq!(chunk.min, PixelPoint { x: 20.0, y: 18.0 });
+        assert_eq!((chunk.width(), chunk.height()), (10.0, 6.0));
+
+        let whole_grid = (Point { x: 0, y: 0 }, Point { x: 9, y: 9 });
+        let normalized = geometry.grid_box_to_normalized(whole_grid);
+        assert_eq!(normalized.min, NormalizedPoint { x: 0.0, y: 0.0 });
+        assert_eq!(normalized.max, NormalizedPoint { x: 1.0, y: 1.0 });
+    }
+
+    #[test]
+    fn distances_are_measured_in_mean_chunk_size() {
+        let geometry = geometry();
+        assert_eq!(geometry.distance_to_grid(Distance::Pixels(16.0)), 2.0);
+        let diagonal = 100f64.hypot(60.0);
+        assert!((geometry.distance_to_grid(Distance::Normalized(0.5)) - diagonal / 16.0).abs() < 1e-9);
+    }
+}
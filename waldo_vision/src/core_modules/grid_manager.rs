@@ -18,14 +18,188 @@
 //     structure (a `Vec<ChunkStatus>`). This "status map" is the final output of
 //     the entire temporal layer and the direct input for the next architectural
 //     layer (the `SmartBlob` spatial analyzer).
-// 4.  **Decoupling**: It decouples the main application logic from the chunk analysis
+// 4.  **Raw Sensor Input**: For Bayer mosaics it skips demosaicing altogether. Each
+//     chunk's average red, green and blue are accumulated directly from the mosaic
+//     sites of that color inside the chunk, producing the same average pixel summary
+//     a fully demosaiced frame would feed to the `SmartChunk`.
+// 5.  **Pixel-Level Refinement**: On request it looks back below chunk resolution,
+//     comparing individual pixels in and around a blob's chunks against their own
+//     luminance in the previous frame to recover the blob's exact foreground and tight
+//     bounds, so textured but static background never leaks into the mask.
+// 6.  **Decoupling**: It decouples the main application logic from the chunk analysis
 //     logic. The main loop will only need to interact with the `GridManager`, giving
 //     it a new frame and receiving a status map, without needing to know the
 //     complex inner workings of the `SmartChunk`s.
 
 use crate::core_modules::chunk::chunk::Chunk;
-use crate::core_modules::pixel::pixel::Pixel;
+use crate::core_modules::pixel::pixel::{BitDepth, Channel16, LuminanceModel, Pixel, Pixel16, PixelData};
+use crate::core_modules::smart_blob::{ForegroundMask, Point};
 use crate::core_modules::smart_chunk::{ChunkStatus, SmartChunk};
+use std::collections::HashSet;
+
+/// The channel layout of a high bit-depth frame buffer of `u16` samples.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Frame16Layout {
+    /// One intensity sample per pixel (thermal and monochrome sensors).
+    Gray16,
+    /// Three samples per pixel: red, green, blue.
+    Rgb48,
+    /// Four samples per pixel: red, green, blue, alpha.
+    Rgba64,
+}
+
+impl Frame16Layout {
+    /// The number of `u16` samples that make up one pixel.
+    pub fn samples_per_pixel(&self) -> usize {
+        match self {
+            Frame16Layout::Gray16 => 1,
+            Frame16Layout::Rgb48 => 3,
+            Frame16Layout::Rgba64 => 4,
+        }
+    }
+}
+
+/// The color filter arrangement of a raw Bayer sensor, named after its top-left 2x2 tile.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum BayerPattern {
+    Rggb,
+    Bggr,
+    Grbg,
+    Gbrg,
+}
+
+impl BayerPattern {
+    /// Returns which color (0 = red, 1 = green, 2 = blue) is sampled at a pixel position.
+    pub fn color_at(&self, x: u32, y: u32) -> usize {
+        let tile = match self {
+            BayerPattern::Rggb => [[0, 1], [1, 2]],
+            BayerPattern::Bggr => [[2, 1], [1, 0]],
+            BayerPattern::Grbg => [[1, 0], [2, 1]],
+            BayerPattern::Gbrg => [[1, 2], [0, 1]],
+        };
+        tile[(y % 2) as usize][(x % 2) as usize]
+    }
+}
+
+/// A borrowed view of an input frame in any of the layouts the grid accepts.
+/// Used by later stages that need to look at the actual pixels of a frame.
+#[derive(Debug, Clone, Copy)]
+pub enum FrameView<'a> {
+    /// Flat RGBA, 4 bytes per pixel.
+    Rgba8(&'a [u8]),
+    /// 16-bit samples in the given layout, with `bit_depth` significant bits.
+    Samples16 {
+        samples: &'a [Channel16],
+        layout: Frame16Layout,
+        bit_depth: BitDepth,
+    },
+    /// 8-bit raw Bayer mosaic, one sample per pixel.
+    Bayer8 { samples: &'a [u8], pattern: BayerPattern },
+    /// High bit-depth raw Bayer mosaic, one sample per pixel.
+    Bayer16 {
+        samples: &'a [Channel16],
+        pattern: BayerPattern,
+        bit_depth: BitDepth,
+    },
+}
+
+impl FrameView<'_> {
+    /// Returns the color of a pixel as an 8-bit `Pixel`. High bit-depth values are
+    /// rescaled to 0-255, and Bayer mosaics are read from the 2x2 tile containing
+    /// the pixel instead of being demosaiced.
+    pub fn pixel_at(&self, x: u32, y: u32, image_width: u32) -> Pixel {
+        let index = (y * image_width + x) as usize;
+        match *self {
+            FrameView::Rgba8(bytes) => Pixel::from(&bytes[index * 4..index * 4 + 4]),
+            FrameView::Samples16 { samples, layout, bit_depth } => {
+                let count = layout.samples_per_pixel();
+                let pixel_samples = &samples[index * count..index * count + count];
+                let narrow = |value: Channel16| to_eight_bit(value as u32, bit_depth);
+                match layout {
+                    Frame16Layout::Gray16 => {
+                        let gray = narrow(pixel_samples[0]);
+                        Pixel::new(gray, gray, gray, u8::MAX)
+                    }
+                    Frame16Layout::Rgb48 => Pixel::new(
+                        narrow(pixel_samples[0]),
+                        narrow(pixel_samples[1]),
+                        narrow(pixel_samples[2]),
+                        u8::MAX,
+                    ),
+                    Frame16Layout::Rgba64 => Pixel::new(
+                        narrow(pixel_samples[0]),
+                        narrow(pixel_samples[1]),
+                        narrow(pixel_samples[2]),
+                        narrow(pixel_samples[3]),
+                    ),
+                }
+            }
+            FrameView::Bayer8 { samples, pattern } => {
+                let read_sample = |i: usize| samples.get(i).map(|v| *v as u32);
+                bayer_tile_pixel(x, y, image_width, pattern, read_sample, BitDepth::Eight)
+            }
+            FrameView::Bayer16 { samples, pattern, bit_depth } => {
+                let read_sample = |i: usize| samples.get(i).map(|v| *v as u32);
+                bayer_tile_pixel(x, y, image_width, pattern, read_sample, bit_depth)
+            }
+        }
+    }
+}
+
+/// Rescales a value with `bit_depth` significant bits to the 0-255 range.
+fn to_eight_bit(value: u32, bit_depth: BitDepth) -> u8 {
+    (value.min(bit_depth.max_value()) * 255 / bit_depth.max_value()) as u8
+}
+
+/// Reads the 2x2 Bayer tile containing a pixel and returns its red, mean green and blue.
+fn bayer_tile_pixel<F>(
+    x: u32,
+    y: u32,
+    image_width: u32,
+    pattern: BayerPattern,
+    read_sample: F,
+    bit_depth: BitDepth,
+) -> Pixel
+where
+    F: Fn(usize) -> Option<u32>,
+{
+    let mut sums = [0u32; 3];
+    let mut counts = [0u32; 3];
+    let (tile_x, tile_y) = (x - x % 2, y - y % 2);
+    for (dx, dy) in [(0, 0), (1, 0), (0, 1), (1, 1)] {
+        let (site_x, site_y) = (tile_x + dx, tile_y + dy);
+        if site_x >= image_width {
+            continue;
+        }
+        if let Some(value) = read_sample((site_y * image_width + site_x) as usize) {
+            let color = pattern.color_at(site_x, site_y);
+            sums[color] += value;
+            counts[color] += 1;
+        }
+    }
+    let channel = |color: usize| to_eight_bit(sums[color] / counts[color].max(1), bit_depth);
+    Pixel::new(channel(0), channel(1), channel(2), u8::MAX)
+}
+
+/// Settings for the optional pixel-accurate foreground refinement of blobs.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct ForegroundRefinement {
+    /// The minimum luminance difference, on the 8-bit scale, between a pixel and its
+    /// own luminance in the previous frame for the pixel to count as foreground.
+    pub luminance_threshold: f64,
+    /// How many chunks around the blob are searched as well, so that parts of the
+    /// object spilling into quiet neighboring chunks are not cut off.
+    pub margin: u32,
+}
+
+impl Default for ForegroundRefinement {
+    fn default() -> Self {
+        Self {
+            luminance_threshold: 25.0,
+            margin: 1,
+        }
+    }
+}
 
 /// Manages the entire grid of `SmartChunk`s and orchestrates the temporal analysis layer.
 pub struct GridManager {
@@ -41,11 +215,33 @@ pub struct GridManager {
     chunk_height: u32,
     /// A flattened vector holding all the stateful `SmartChunk` analyzers, one for each grid position.
     smart_chunks: Vec<SmartChunk>,
+    /// The model used to measure luminance, shared by every chunk.
+    luminance_model: LuminanceModel,
+    /// The 8-bit scale luminance of every pixel in the last remembered frame, the
+    /// reference of foreground masks. Empty until `remember_pixels` is first called.
+    previous_luminance: Vec<f32>,
 }
 
 impl GridManager {
     /// Creates a new GridManager for a given image dimension and chunk size.
     pub fn new(image_width: u32, image_height: u32, chunk_width: u32, chunk_height: u32) -> Self {
+        Self::with_luminance_model(
+            image_width,
+            image_height,
+            chunk_width,
+            chunk_height,
+            LuminanceModel::default(),
+        )
+    }
+
+    /// Creates a new GridManager whose chunks measure luminance with the given model.
+    pub fn with_luminance_model(
+        image_width: u32,
+        image_height: u32,
+        chunk_width: u32,
+        chunk_height: u32,
+        luminance_model: LuminanceModel,
+    ) -> Self {
         let grid_width = image_width / chunk_width;
         let grid_height = image_height / chunk_height;
         let num_chunks = (grid_width * grid_height) as usize;
@@ -55,7 +251,7 @@ impl GridManager {
         for i in 0..num_chunks {
             let y = i as u32 / grid_width;
             let x = i as u32 % grid_width;
-            smart_chunks.push(SmartChunk::new(x, y));
+            smart_chunks.push(SmartChunk::with_luminance_model(x, y, luminance_model));
         }
 
         Self {
@@ -65,12 +261,215 @@ impl GridManager {
             chunk_width,
             chunk_height,
             smart_chunks,
+            luminance_model,
+            previous_luminance: Vec::new(),
+        }
+    }
+
+    /// Processes a frame given in any of the supported layouts.
+    pub fn process_view(&mut self, frame: FrameView) -> Vec<ChunkStatus> {
+        match frame {
+            FrameView::Rgba8(bytes) => self.process_frame(bytes),
+            FrameView::Samples16 { samples, layout, bit_depth } => self.process_frame16(samples, layout, bit_depth),
+            FrameView::Bayer8 { samples, pattern } => self.process_bayer_frame(samples, pattern),
+            FrameView::Bayer16 { samples, pattern, bit_depth } => self.process_bayer_frame16(samples, pattern, bit_depth),
+        }
+    }
+
+    /// Computes the pixel-accurate foreground of a blob. Every pixel of the blob's chunks,
+    /// and of the chunks within `margin` of them, is compared with its own luminance in
+    /// the last frame given to `remember_pixels`, so static texture is never foreground.
+    /// The mask is empty until a frame has been remembered.
+    pub fn foreground_mask(
+        &self,
+        frame: &FrameView,
+        chunk_coords: &[Point],
+        refinement: &ForegroundRefinement,
+    ) -> ForegroundMask {
+        let margin = refinement.margin;
+        let mut searched_chunks = HashSet::new();
+        for chunk in chunk_coords {
+            let (min_x, min_y) = (chunk.x.saturating_sub(margin), chunk.y.saturating_sub(margin));
+            let max_x = (chunk.x + margin).min(self.grid_width - 1);
+            let max_y = (chunk.y + margin).min(self.grid_height - 1);
+            for y in min_y..=max_y {
+                for x in min_x..=max_x {
+                    searched_chunks.insert(Point { x, y });
+                }
+            }
+        }
+
+        let min_chunk_x = searched_chunks.iter().map(|p| p.x).min().unwrap_or(0);
+        let min_chunk_y = searched_chunks.iter().map(|p| p.y).min().unwrap_or(0);
+        let max_chunk_x = searched_chunks.iter().map(|p| p.x + 1).max().unwrap_or(0);
+        let max_chunk_y = searched_chunks.iter().map(|p| p.y + 1).max().unwrap_or(0);
+        let origin = Point {
+            x: min_chunk_x * self.chunk_width,
+            y: min_chunk_y * self.chunk_height,
+        };
+        let width = (max_chunk_x - min_chunk_x) * self.chunk_width;
+        let height = (max_chunk_y - min_chunk_y) * self.chunk_height;
+
+        let mut mask = vec![false; (width * height) as usize];
+        let mut pixel_count = 0;
+        let mut bounds: Option<(Point, Point)> = None;
+        let has_reference = !self.previous_luminance.is_empty();
+        for chunk in searched_chunks.iter().filter(|_| has_reference) {
+            for dy in 0..self.chunk_height {
+                for dx in 0..self.chunk_width {
+                    let x = chunk.x * self.chunk_width + dx;
+                    let y = chunk.y * self.chunk_height + dy;
+                    let luminance = self.luminance_model.luminance(&frame.pixel_at(x, y, self.image_width));
+                    let reference = self.previous_luminance[(y * self.image_width + x) as usize] as f64;
+                    if (luminance - reference).abs() <= refinement.luminance_threshold {
+                        continue;
+                    }
+
+                    mask[((y - origin.y) * width + (x - origin.x)) as usize] = true;
+                    pixel_count += 1;
+                    bounds = Some(match bounds {
+                        None => (Point { x, y }, Point { x, y }),
+                        Some((min, max)) => (
+                            Point { x: min.x.min(x), y: min.y.min(y) },
+                            Point { x: max.x.max(x), y: max.y.max(y) },
+                        ),
+                    });
+                }
+            }
+        }
+
+        ForegroundMask {
+            origin,
+            width,
+            height,
+            mask,
+            pixel_count,
+            bounding_box: bounds,
+        }
+    }
+
+    /// Stores the luminance of every pixel covered by the grid as the reference for the
+    /// foreground masks of the next frame.
+    pub fn remember_pixels(&mut self, frame: &FrameView) {
+        let image_height = self.grid_height * self.chunk_height;
+        self.previous_luminance.resize((self.image_width * image_height) as usize, 0.0);
+        for y in 0..image_height {
+            for x in 0..self.grid_width * self.chunk_width {
+                let luminance = self.luminance_model.luminance(&frame.pixel_at(x, y, self.image_width));
+                self.previous_luminance[(y * self.image_width + x) as usize] = luminance as f32;
+            }
         }
     }
 
     /// The main entry point for the vision system.
     /// Takes a raw RGBA image buffer, processes it, and returns a map of chunk statuses.
     pub fn process_frame(&mut self, frame_buffer: &[u8]) -> Vec<ChunkStatus> {
+        self.process_chunks(BitDepth::Eight, |pixel_index| {
+            let byte_index = pixel_index * 4;
+            Pixel::from(&frame_buffer[byte_index..byte_index + 4])
+        })
+    }
+
+    /// The entry point for high bit-depth sources such as thermal and HDR cameras.
+    /// Takes a buffer of `u16` samples in the given layout, whose values use `bit_depth`
+    /// significant bits, and returns a map of chunk statuses computed at full precision.
+    pub fn process_frame16(
+        &mut self,
+        frame_buffer: &[Channel16],
+        layout: Frame16Layout,
+        bit_depth: BitDepth,
+    ) -> Vec<ChunkStatus> {
+        let samples = layout.samples_per_pixel();
+        let opaque = bit_depth.max_value() as Channel16;
+        self.process_chunks(bit_depth, |pixel_index| {
+            let sample_index = pixel_index * samples;
+            let pixel_samples = &frame_buffer[sample_index..sample_index + samples];
+            match layout {
+                Frame16Layout::Gray16 => Pixel16::gray(pixel_samples[0], bit_depth),
+                Frame16Layout::Rgb48 => {
+                    Pixel16::new(pixel_samples[0], pixel_samples[1], pixel_samples[2], opaque)
+                }
+                Frame16Layout::Rgba64 => Pixel16::from(pixel_samples),
+            }
+        })
+    }
+
+    /// The entry point for 8-bit raw Bayer frames (one sample per pixel).
+    /// Chunk averages are computed directly from the mosaic, without demosaicing.
+    pub fn process_bayer_frame(&mut self, frame_buffer: &[u8], pattern: BayerPattern) -> Vec<ChunkStatus> {
+        self.process_bayer_chunks(pattern, BitDepth::Eight, |pixel_index| frame_buffer[pixel_index] as u32)
+    }
+
+    /// The entry point for high bit-depth raw Bayer frames (one `u16` sample per pixel).
+    pub fn process_bayer_frame16(
+        &mut self,
+        frame_buffer: &[Channel16],
+        pattern: BayerPattern,
+        bit_depth: BitDepth,
+    ) -> Vec<ChunkStatus> {
+        self.process_bayer_chunks(pattern, bit_depth, |pixel_index| frame_buffer[pixel_index] as u32)
+    }
+
+    /// Accumulates the mosaic sites of each chunk by color and updates every `SmartChunk`
+    /// with the resulting average pixel. The `read_sample` closure maps a pixel's index in
+    /// the frame (row-major) to its raw sensor value.
+    fn process_bayer_chunks<F>(&mut self, pattern: BayerPattern, bit_depth: BitDepth, read_sample: F) -> Vec<ChunkStatus>
+    where
+        F: Fn(usize) -> u32,
+    {
+        for chunk_index in 0..self.smart_chunks.len() {
+            let average_pixel = self.bayer_chunk_average(chunk_index, pattern, bit_depth, &read_sample);
+            self.smart_chunks[chunk_index].update_with_average(&average_pixel, bit_depth);
+        }
+
+        self.smart_chunks
+            .iter()
+            .map(|sc| sc.status.clone())
+            .collect()
+    }
+
+    /// Averages the mosaic sites of one chunk separately for each color, in the chunk's
+    /// luminance model, and returns the result as an opaque pixel of the given depth.
+    fn bayer_chunk_average<F>(&self, chunk_index: usize, pattern: BayerPattern, bit_depth: BitDepth, read_sample: &F) -> Pixel16
+    where
+        F: Fn(usize) -> u32,
+    {
+        let chunk_y = chunk_index as u32 / self.grid_width;
+        let chunk_x = chunk_index as u32 % self.grid_width;
+        let start_pixel_x = chunk_x * self.chunk_width;
+        let start_pixel_y = chunk_y * self.chunk_height;
+        let model = self.smart_chunks[chunk_index].luminance_model;
+
+        let mut sums = [0.0; 3];
+        let mut counts = [0u32; 3];
+        for i in 0..(self.chunk_width * self.chunk_height) {
+            let pixel_x = start_pixel_x + i % self.chunk_width;
+            let pixel_y = start_pixel_y + i / self.chunk_width;
+            let color = pattern.color_at(pixel_x, pixel_y);
+
+            let pixel_index = (pixel_y * self.image_width) + pixel_x;
+            sums[color] += model.channel_value_at(read_sample(pixel_index as usize), bit_depth);
+            counts[color] += 1;
+        }
+
+        // A 1-pixel-wide chunk may not contain every color; such channels read as zero.
+        let average = |color: usize| {
+            if counts[color] == 0 {
+                0
+            } else {
+                model.encode_at(sums[color] / counts[color] as f64, bit_depth)
+            }
+        };
+        Pixel16::from_channels([average(0), average(1), average(2), bit_depth.max_value()])
+    }
+
+    /// Slices the frame into chunks and updates every `SmartChunk`. The `read_pixel`
+    /// closure maps a pixel's index in the frame (row-major) to its pixel data.
+    fn process_chunks<P, F>(&mut self, bit_depth: BitDepth, read_pixel: F) -> Vec<ChunkStatus>
+    where
+        P: PixelData,
+        F: Fn(usize) -> P,
+    {
         // This is the flattened loop to iterate over chunks that we designed earlier.
         for chunk_index in 0..self.smart_chunks.len() {
             let chunk_y = chunk_index as u32 / self.grid_width;
@@ -85,7 +484,7 @@ impl GridManager {
 
             // This is the optimized, single flattened loop for extracting pixels for a chunk.
             // It iterates through each pixel position within the chunk's boundaries and calculates
-            // its exact index in the main frame_buffer, avoiding intermediate row-based slices.
+            // its exact index in the main frame buffer, avoiding intermediate row-based slices.
             for i in 0..(self.chunk_width * self.chunk_height) {
                 let y_offset = i / self.chunk_width;
                 let x_offset = i % self.chunk_width;
@@ -93,16 +492,14 @@ impl GridManager {
                 let pixel_y = start_pixel_y + y_offset;
                 let pixel_x = start_pixel_x + x_offset;
 
-                let byte_index = ((pixel_y * self.image_width) + pixel_x) * 4;
-
-                let pixel_bytes = &frame_buffer[byte_index as usize..(byte_index + 4) as usize];
-                chunk_pixels.push(Pixel::from(pixel_bytes));
+                let pixel_index = (pixel_y * self.image_width) + pixel_x;
+                chunk_pixels.push(read_pixel(pixel_index as usize));
             }
 
             let chunk_data = Chunk::new(self.chunk_width, self.chunk_height, chunk_pixels);
 
             // Update the corresponding SmartChunk with the new data for its location.
-            self.smart_chunks[chunk_index].update(&chunk_data);
+            self.smart_chunks[chunk_index].update_at_depth(&chunk_data, bit_depth);
         }
 
         // After all chunks are updated, collect their new statuses to create the final status map.
@@ -112,3 +509,115 @@ impl GridManager {
             .collect()
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const SIZE: u32 = 16;
+
+    /// A checkerboard of black and light-gray pixels, with `square` painted white.
+    fn textured_frame(square: Option<(u32, u32, u32)>) -> Vec<u8> {
+        let mut bytes = Vec::with_capacity((SIZE * SIZE * 4) as usize);
+        for y in 0..SIZE {
+            for x in 0..SIZE {
+                let inside = square.is_some_and(|(left, top, side)| {
+                    (left..left + side).contains(&x) && (top..top + side).contains(&y)
+                });
+                let value = if inside { 255 } else if (x + y) % 2 == 0 { 0 } else { 200 };
+                bytes.extend_from_slice(&[value, value, value, 255]);
+            }
+        }
+        bytes
+    }
+
+    fn observe(manager: &mut GridManager, bytes: &[u8]) {
+        manager.process_frame(bytes);
+        manager.remember_pixels(&FrameView::Rgba8(bytes));
+    }
+
+    #[test]
+    fn textured_static_background_is_not_foreground() {
+        let mut manager = GridManager::new(SIZE, SIZE, 4, 4);
+        let background = textured_frame(None);
+        observe(&mut manager, &background);
+        observe(&mut manager, &background);
+
+        let chunks = [Point { x: 1, y: 1 }];
+        let mask = manager.foreground_mask(&FrameView::Rgba8(&background), &chunks, &ForegroundRefinement::default());
+        assert_eq!(mask.pixel_count, 0);
+        assert_eq!(mask.bounding_box, None);
+
+        let object = textured_frame(Some((5, 5, 3)));
+        manager.process_frame(&object);
+        let mask = manager.foreground_mask(&FrameView::Rgba8(&object), &chunks, &ForegroundRefinement::default());
+        assert_eq!(mask.pixel_count, 9);
+        assert_eq!(mask.bounding_box, Some((Point { x: 5, y: 5 }, Point { x: 7, y: 7 })));
+    }
+
+    #[test]
+    fn mask_is_empty_before_a_frame_is_remembered() {
+        let mut manager = GridManager::new(SIZE, SIZE, 4, 4);
+        let object = textured_frame(Some((5, 5, 3)));
+        manager.process_frame(&object);
+        let mask = manager.foreground_mask(
+            &FrameView::Rgba8(&object),
+            &[Point { x: 1, y: 1 }],
+            &ForegroundRefinement::default(),
+        );
+        assert_eq!(mask.pixel_count, 0);
+    }
+
+    /// An RGGB mosaic where red sites read 180, blue sites 60, and green sites 90 on
+    /// red rows and 30 on blue rows, so a green average shows which sites it counted.
+    fn rggb_mosaic(width: u32, height: u32) -> Vec<u8> {
+        let mut samples = Vec::with_capacity((width * height) as usize);
+        for y in 0..height {
+            for x in 0..width {
+                samples.push(match BayerPattern::Rggb.color_at(x, y) {
+                    0 => 180,
+                    2 => 60,
+                    _ if y % 2 == 0 => 90,
+                    _ => 30,
+                });
+            }
+        }
+        samples
+    }
+
+    fn mosaic_averages(manager: &GridManager, samples: &[u8]) -> Vec<[u32; 4]> {
+        let read_sample = |index: usize| samples[index] as u32;
+        (0..manager.smart_chunks.len())
+            .map(|chunk_index| {
+                manager
+                    .bayer_chunk_average(chunk_index, BayerPattern::Rggb, BitDepth::Eight, &read_sample)
+                    .channels()
+            })
+            .collect()
+    }
+
+    #[test]
+    fn rggb_patch_averages_each_color_separately() {
+        let samples = rggb_mosaic(4, 4);
+        let manager = GridManager::new(4, 4, 4, 4);
+        assert_eq!(mosaic_averages(&manager, &samples), [[180, 60, 60, 255]]);
+
+        // A single pixel reads its own 2x2 tile, whichever site it lands on.
+        let view = FrameView::Bayer8 { samples: &samples, pattern: BayerPattern::Rggb };
+        assert_eq!(view.pixel_at(0, 0, 4), Pixel::new(180, 60, 60, 255));
+        assert_eq!(view.pixel_at(3, 3, 4), Pixel::new(180, 60, 60, 255));
+    }
+
+    #[test]
+    fn odd_sized_chunks_weight_every_site_equally() {
+        // 3x3 chunks do not align with the 2x2 tiles, so each chunk holds a different
+        // number of red, green and blue sites.
+        let samples = rggb_mosaic(6, 6);
+        let mut manager = GridManager::new(6, 6, 3, 3);
+        assert_eq!(
+            mosaic_averages(&manager, &samples),
+            [[180, 60, 60, 255], [180, 78, 60, 255], [180, 42, 60, 255], [180, 60, 60, 255]]
+        );
+        assert_eq!(manager.process_bayer_frame(&samples, BayerPattern::Rggb).len(), 4);
+    }
+}
@@ -0,0 +1,278 @@
+// THEORY:
+// The `kalman` module gives each track a statistical motion model. Blob centroids are
+// noisy: the center of mass jumps by a chunk when a limb enters or leaves a chunk, and
+// predicting the next position from the last frame's raw velocity amplifies that
+// noise. A Kalman filter blends each new measurement with the motion predicted so far,
+// weighted by how much each is trusted.
+//
+// Key architectural principles:
+// 1.  **Constant-Velocity Model**: The state is position and velocity on the chunk
+//     grid, `[x, y, vx, vy]`. Between frames the object is assumed to keep its
+//     velocity, with random accelerations accounted for as process noise.
+// 2.  **Uncertainty as a First-Class Output**: The filter tracks the covariance of
+//     its state. The predicted covariance grows while an object goes unseen and
+//     shrinks as measurements arrive, so consumers can tell a confident estimate from
+//     a guess.
+// 3.  **Mahalanobis Gating**: Whether a detection could belong to a track is judged by
+//     its Mahalanobis distance from the prediction, which scales the gate with the
+//     track's uncertainty (a fast or long-unseen object gets a wider gate) instead of
+//     using one fixed radius for all tracks.
+
+/// A 4x4 matrix over the state `[x, y, vx, vy]`.
+type Matrix4 = [[f64; 4]; 4];
+
+/// Noise settings and the association gate of the Kalman motion model.
+///
+/// The filter steps one frame at a time, so the noise settings are per frame and are
+/// not scaled by `TrackerConfig::frame_rate`: the same settings allow twice the
+/// acceleration per second at 60 fps as at 30 fps. Retune them when the frame rate
+/// changes.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct KalmanConfig {
+    /// The variance of the random acceleration between frames (chunks^2 / frame^4, per
+    /// frame).
+    pub process_noise: f64,
+    /// The variance of a measured center of mass (chunks^2).
+    pub measurement_noise: f64,
+    /// The velocity variance of a new track, whose velocity is not known yet
+    /// (chunks^2 / frame^2). It bounds how fast an object can move on its second frame
+    /// and still be associated with its track.
+    pub initial_velocity_variance: f64,
+    /// The largest squared Mahalanobis distance at which a detection may still be
+    /// associated with a track. 9.21 admits 99% of true detections.
+    pub gate: f64,
+}
+
+impl Default for KalmanConfig {
+    fn default() -> Self {
+        Self {
+            process_noise: 0.05,
+            measurement_noise: 0.25,
+            initial_velocity_variance: 4.0,
+            gate: 9.21,
+        }
+    }
+}
+
+/// A constant-velocity Kalman filter over a track's position on the chunk grid.
+#[derive(Debug, Clone, PartialEq)]
+pub struct KalmanFilter {
+    /// The state estimate `[x, y, vx, vy]`.
+    state: [f64; 4],
+    /// The covariance of the state estimate.
+    covariance: Matrix4,
+    config: KalmanConfig,
+}
+
+impl KalmanFilter {
+    /// Starts a filter at a measured position with unknown velocity.
+    pub fn new(position: (f64, f64), config: KalmanConfig) -> Self {
+        let mut covariance = [[0.0; 4]; 4];
+        covariance[0][0] = config.measurement_noise;
+        covariance[1][1] = config.measurement_noise;
+        covariance[2][2] = config.initial_velocity_variance;
+        covariance[3][3] = config.initial_velocity_variance;
+        Self {
+            state: [position.0, position.1, 0.0, 0.0],
+            covariance,
+            config,
+        }
+    }
+
+    /// Advances the estimate by one frame.
+    pub fn predict(&mut self) {
+        let [x, y, vx, vy] = self.state;
+        self.state = [x + vx, y + vy, vx, vy];
+
+        // P = F P F^T, with F adding velocity to position.
+        let p = &mut self.covariance;
+        for row in p.iter_mut() {
+            row[0] += row[2];
+            row[1] += row[3];
+        }
+        let (position_rows, velocity_rows) = p.split_at_mut(2);
+        for (position_row, velocity_row) in position_rows.iter_mut().zip(velocity_rows.iter()) {
+            for (value, velocity_value) in position_row.iter_mut().zip(velocity_row) {
+                *value += velocity_value;
+            }
+        }
+
+        // Q for a white random acceleration with variance q: position grows with
+        // dt^4 / 4, velocity with dt^2, and their covariance with dt^3 / 2 (dt = 1).
+        let q = self.config.process_noise;
+        for axis in 0..2 {
+            p[axis][axis] += q / 4.0;
+            p[axis][axis + 2] += q / 2.0;
+            p[axis + 2][axis] += q / 2.0;
+            p[axis + 2][axis + 2] += q;
+        }
+    }
+
+    /// Corrects the estimate with a measured position.
+    pub fn update(&mut self, measurement: (f64, f64)) {
+        let innovation = [measurement.0 - self.state[0], measurement.1 - self.state[1]];
+        let s_inverse = invert2(self.innovation_covariance());
+        let p = self.covariance;
+
+        // K = P H^T S^-1, where P H^T is the first two columns of P.
+        let mut gain = [[0.0; 2]; 4];
+        for (row, gain_row) in gain.iter_mut().enumerate() {
+            for column in 0..2 {
+                gain_row[column] = p[row][0] * s_inverse[0][column] + p[row][1] * s_inverse[1][column];
+            }
+        }
+
+        for (row, gain_row) in gain.iter().enumerate() {
+            self.state[row] += gain_row[0] * innovation[0] + gain_row[1] * innovation[1];
+        }
+        // P = (I - K H) P, where H P is the first two rows of P.
+        for (row, (gain_row, covariance_row)) in gain.iter().zip(self.covariance.iter_mut()).enumerate() {
+            for (column, value) in covariance_row.iter_mut().enumerate() {
+                *value = p[row][column] - gain_row[0] * p[0][column] - gain_row[1] * p[1][column];
+            }
+        }
+    }
+
+    /// The squared Mahalanobis distance of a measured position from the current estimate.
+    pub fn mahalanobis_distance_squared(&self, measurement: (f64, f64)) -> f64 {
+        let dx = measurement.0 - self.state[0];
+        let dy = measurement.1 - self.state[1];
+        let s_inverse = invert2(self.innovation_covariance());
+        dx * (s_inverse[0][0] * dx + s_inverse[0][1] * dy) + dy * (s_inverse[1][0] * dx + s_inverse[1][1] * dy)
+    }
+
+    /// The gate distance of a measured position: 0.0 at the estimate and 1.0 at the edge
+    /// of the gate, or `None` if the measurement falls outside the gate.
+    pub fn gate_distance(&self, measurement: (f64, f64)) -> Option<f64> {
+        let distance_squared = self.mahalanobis_distance_squared(measurement);
+        (distance_squared < self.config.gate).then(|| (distance_squared / self.config.gate).sqrt())
+    }
+
+    /// The estimated position.
+    pub fn position(&self) -> (f64, f64) {
+        (self.state[0], self.state[1])
+    }
+
+    /// The estimated velocity, in chunks per frame.
+    pub fn velocity(&self) -> (f64, f64) {
+        (self.state[2], self.state[3])
+    }
+
+    /// The full state estimate `[x, y, vx, vy]`.
+    pub fn state(&self) -> [f64; 4] {
+        self.state
+    }
+
+    /// The covariance of the full state estimate.
+    pub fn covariance(&self) -> [[f64; 4]; 4] {
+        self.covariance
+    }
+
+    /// The standard deviation of the estimated position along x and y.
+    pub fn position_std_dev(&self) -> (f64, f64) {
+        (self.covariance[0][0].max(0.0).sqrt(), self.covariance[1][1].max(0.0).sqrt())
+    }
+
+    /// The standard deviation of the estimated velocity along x and y.
+    pub fn velocity_std_dev(&self) -> (f64, f64) {
+        (self.covariance[2][2].max(0.0).sqrt(), self.covariance[3][3].max(0.0).sqrt())
+    }
+
+    /// S = H P H^T + R: the expected covariance of the next measurement.
+    fn innovation_covariance(&self) -> [[f64; 2]; 2] {
+        let r = self.config.measurement_noise;
+        [
+            [self.covariance[0][0] + r, self.covariance[0][1]],
+            [self.covariance[1][0], self.covariance[1][1] + r],
+        ]
+    }
+}
+
+fn invert2(m: [[f64; 2]; 2]) -> [[f64; 2]; 2] {
+    let determinant = (m[0][0] * m[1][1] - m[0][1] * m[1][0]).max(f64::EPSILON);
+    [
+        [m[1][1] / determinant, -m[0][1] / determinant],
+        [-m[1][0] / determinant, m[0][0] / determinant],
+    ]
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Runs the filter over a constant-velocity path for the given number of frames.
+    fn follow(start: (f64, f64), velocity: (f64, f64), frames: u32) -> KalmanFilter {
+        let mut filter = KalmanFilter::new(start, KalmanConfig::default());
+        for frame in 1..=frames {
+            filter.predict();
+            filter.update((start.0 + velocity.0 * frame as f64, start.1 + velocity.1 * frame as f64));
+        }
+        filter
+    }
+
+    #[test]
+    fn converges_on_constant_velocity() {
+        let filter = follow((10.0, 20.0), (0.5, -0.25), 40);
+        let (vx, vy) = filter.velocity();
+        assert!((vx - 0.5).abs() < 1e-3 && (vy + 0.25).abs() < 1e-3, "velocity {vx}, {vy}");
+        let (x, y) = filter.position();
+        assert!((x - 30.0).abs() < 1e-3 && (y - 10.0).abs() < 1e-3, "position {x}, {y}");
+
+        // Measurements shrink the uncertainty below that of a single measurement.
+        let (std_x, std_y) = filter.position_std_dev();
+        assert!(std_x < KalmanConfig::default().measurement_noise.sqrt());
+        assert_eq!(std_x, std_y);
+    }
+
+    #[test]
+    fn prediction_follows_the_velocity() {
+        let mut filter = follow((10.0, 20.0), (0.5, -0.25), 40);
+        filter.predict();
+        let (x, y) = filter.position();
+        assert!((x - 30.5).abs() < 1e-3 && (y - 9.75).abs() < 1e-3, "prediction {x}, {y}");
+    }
+
+    #[test]
+    fn covariance_grows_while_coasting() {
+        let mut filter = follow((10.0, 20.0), (0.5, 0.0), 40);
+        let mut previous = filter.covariance();
+        for _ in 0..10 {
+            filter.predict();
+            let covariance = filter.covariance();
+            assert!(covariance[0][0] > previous[0][0]);
+            assert!(covariance[1][1] > previous[1][1]);
+            // Without measurements the velocity variance grows by the process noise.
+            let growth = covariance[2][2] - previous[2][2];
+            assert!((growth - KalmanConfig::default().process_noise).abs() < 1e-12);
+            previous = covariance;
+        }
+
+        // A measurement shrinks it again.
+        filter.update(filter.position());
+        assert!(filter.covariance()[0][0] < previous[0][0]);
+    }
+
+    #[test]
+    fn mahalanobis_gate_of_a_new_track() {
+        // A new track expects its next measurement with variance 0.25 + 0.25 per axis.
+        let filter = KalmanFilter::new((10.0, 10.0), KalmanConfig::default());
+        assert_eq!(filter.gate_distance((10.0, 10.0)), Some(0.0));
+        assert!((filter.mahalanobis_distance_squared((12.0, 10.0)) - 8.0).abs() < 1e-12);
+        assert!((filter.mahalanobis_distance_squared((11.0, 11.0)) - 4.0).abs() < 1e-12);
+
+        let inside = filter.gate_distance((12.0, 10.0)).unwrap();
+        assert!((inside - (8.0f64 / 9.21).sqrt()).abs() < 1e-12);
+        assert_eq!(filter.gate_distance((12.2, 10.0)), None);
+    }
+
+    #[test]
+    fn gate_widens_while_coasting() {
+        let mut filter = follow((10.0, 10.0), (0.0, 0.0), 20);
+        let far = (13.0, 10.0);
+        assert_eq!(filter.gate_distance(far), None);
+        for _ in 0..15 {
+            filter.predict();
+        }
+        assert!(filter.gate_distance(far).is_some());
+    }
+}
@@ -1,7 +1,15 @@
+pub mod appearance;
+pub mod assignment;
 pub mod blob_detector;
+pub mod blob_filter;
 pub mod chunk;
+pub mod frame_decoder;
+pub mod geometry;
 pub mod grid_manager;
+pub mod kalman;
 pub mod moment;
+pub mod morphology;
+pub mod perspective;
 pub mod pixel;
 pub mod smart_blob;
 pub mod smart_chunk;
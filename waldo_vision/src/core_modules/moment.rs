@@ -3,9 +3,17 @@
 // forming the core of the Behavioral Analysis Layer. Its purpose is to transform the
 // continuous stream of tracked object data into a discrete, historical narrative of
 // events, called "Moments."
+//
+// Moments are linked to each other when their objects split or merge: a moment lists
+// the moments its object descends from and the moments that descend from it, so a
+// group that separates, or a person getting into a car, reads as one connected story.
 
+use crate::core_modules::geometry::{FrameGeometry, NormalizedPoint, PixelPoint};
 use crate::core_modules::smart_blob::SmartBlob;
-use crate::core_modules::tracker::{TrackedBlob, Tracker, TrackedState};
+use crate::core_modules::tracker::{
+    AnomalyReason, AssociationCandidate, LineageEvent, TrackEvent, TrackRecovered, TrackedBlob, Tracker,
+    TrackedState,
+};
 use crate::pipeline::PipelineConfig;
 use std::collections::HashSet;
 
@@ -14,14 +22,35 @@ use std::collections::HashSet;
 pub struct Moment {
     pub id: u64,
     pub start_frame: u64,
+    /// The last frame in which the object was observed.
     pub end_frame: u64,
+    /// The object's center of mass in each frame, in grid units; see `pixel_path` and
+    /// `normalized_path`.
     pub path: Vec<(f64, f64)>,
     pub blob_history: Vec<SmartBlob>,
     pub is_active: bool,
     pub is_significant: bool,
+    /// The moments this one descends from: the one its object split off from, or the
+    /// ones whose objects merged into it.
+    pub parent_ids: Vec<u64>,
+    /// The moments that descend from this one by a split or a merge.
+    pub child_ids: Vec<u64>,
+    /// Why the object was judged anomalous during the moment: the strongest reason
+    /// seen for each kind of anomaly, strongest first.
+    pub anomaly_reasons: Vec<AnomalyReason>,
 }
 
 impl Moment {
+    /// The object's path in pixels.
+    pub fn pixel_path(&self, geometry: &FrameGeometry) -> Vec<PixelPoint> {
+        self.path.iter().map(|p| geometry.grid_to_pixel(*p)).collect()
+    }
+
+    /// The object's path relative to the image size.
+    pub fn normalized_path(&self, geometry: &FrameGeometry) -> Vec<NormalizedPoint> {
+        self.path.iter().map(|p| geometry.grid_to_normalized(*p)).collect()
+    }
+
     fn new(tracked_blob: &TrackedBlob, start_frame: u64) -> Self {
         Self {
             id: tracked_blob.id,
@@ -31,6 +60,9 @@ impl Moment {
             blob_history: vec![tracked_blob.latest_blob.clone()],
             is_active: true,
             is_significant: false,
+            parent_ids: Vec::new(),
+            child_ids: Vec::new(),
+            anomaly_reasons: tracked_blob.anomaly_reasons.clone(),
         }
     }
 
@@ -38,6 +70,15 @@ impl Moment {
         self.end_frame = current_frame;
         self.path.push(tracked_blob.latest_blob.center_of_mass);
         self.blob_history.push(tracked_blob.latest_blob.clone());
+
+        for reason in &tracked_blob.anomaly_reasons {
+            match self.anomaly_reasons.iter_mut().find(|known| known.kind == reason.kind) {
+                Some(known) if known.score < reason.score => *known = *reason,
+                Some(_) => {}
+                None => self.anomaly_reasons.push(*reason),
+            }
+        }
+        self.anomaly_reasons.sort_by(|a, b| b.score.total_cmp(&a.score));
     }
 
     fn complete(&mut self) {
@@ -72,6 +113,11 @@ impl SceneManager {
 
         for tracked_blob in tracked_blobs {
             current_tracked_ids.insert(tracked_blob.id);
+            // A coasting track was not observed; its moment ends at its last sighting
+            // unless the track is recovered.
+            if tracked_blob.frames_since_seen > 0 {
+                continue;
+            }
 
             let moment = if let Some(m) = self.active_moments.iter_mut().find(|m| m.id == tracked_blob.id) {
                 m.update(tracked_blob, self.frame_count);
@@ -86,6 +132,22 @@ impl SceneManager {
             moment.is_significant = tracked_blob.state == TrackedState::New || tracked_blob.state == TrackedState::Anomalous;
         }
 
+        // Link before completing, so moments that end in a merge are returned linked.
+        for event in self.tracker.get_lineage_events().clone() {
+            match event {
+                LineageEvent::Split { parent_id, child_ids } => {
+                    for child_id in child_ids {
+                        self.link_moments(parent_id, child_id, &mut newly_started_moments);
+                    }
+                }
+                LineageEvent::Merge { parent_ids, child_id } => {
+                    for parent_id in parent_ids {
+                        self.link_moments(parent_id, child_id, &mut newly_started_moments);
+                    }
+                }
+            }
+        }
+
         let mut still_active = Vec::new();
         let mut newly_completed_moments = Vec::new();
 
@@ -102,6 +164,20 @@ impl SceneManager {
         self.active_moments = still_active;
         (newly_started_moments, newly_completed_moments)
     }
+
+    /// Records that the moment `child_id` descends from the moment `parent_id`, in the
+    /// stored moments and in the copies about to be returned as newly started.
+    fn link_moments(&mut self, parent_id: u64, child_id: u64, newly_started_moments: &mut [Moment]) {
+        let stored = self.active_moments.iter_mut().chain(self.completed_moments.iter_mut());
+        for moment in stored.chain(newly_started_moments.iter_mut()) {
+            if moment.id == child_id && !moment.parent_ids.contains(&parent_id) {
+                moment.parent_ids.push(parent_id);
+            }
+            if moment.id == parent_id && !moment.child_ids.contains(&child_id) {
+                moment.child_ids.push(child_id);
+            }
+        }
+    }
     
     pub fn get_active_moments(&self) -> &Vec<Moment> {
         &self.active_moments
@@ -114,4 +190,138 @@ impl SceneManager {
     pub fn get_tracked_blobs(&self) -> &Vec<TrackedBlob> {
         self.tracker.get_tracked_blobs()
     }
-}
\ No newline at end of file
+
+    pub fn get_association_diagnostics(&self) -> &Vec<AssociationCandidate> {
+        self.tracker.get_association_diagnostics()
+    }
+
+    pub fn get_lineage_events(&self) -> &Vec<LineageEvent> {
+        self.tracker.get_lineage_events()
+    }
+
+    pub fn get_recovered_tracks(&self) -> &Vec<TrackRecovered> {
+        self.tracker.get_recovered_tracks()
+    }
+
+    pub fn get_track_events(&self) -> &Vec<TrackEvent> {
+        self.tracker.get_track_events()
+    }
+}
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::core_modules::tracker::tests::{detect, tracker_config};
+    use crate::core_modules::tracker::TrackAssignment;
+
+    fn moment(moments: &[Moment], id: u64) -> &Moment {
+        moments.iter().find(|moment| moment.id == id).expect("no moment with the given ID")
+    }
+
+    #[test]
+    fn recovered_track_continues_its_moment_without_coasted_entries() {
+        let config = tracker_config(TrackAssignment::Hungarian);
+        let mut scene = SceneManager::new();
+        for _ in 0..3 {
+            scene.update(detect(&[(10, 20, 2, 2)]), &config);
+        }
+        for _ in 0..3 {
+            let (started, completed) = scene.update(Vec::new(), &config);
+            assert!(started.is_empty() && completed.is_empty());
+            assert_eq!(scene.get_active_moments()[0].end_frame, 3);
+        }
+
+        let (started, completed) = scene.update(detect(&[(10, 20, 2, 2)]), &config);
+        assert!(started.is_empty() && completed.is_empty());
+        assert_eq!(scene.get_recovered_tracks(), &vec![TrackRecovered { track_id: 0, frames_unseen: 3 }]);
+
+        let moments = scene.get_active_moments();
+        assert_eq!(moments.len(), 1);
+        assert_eq!((moments[0].start_frame, moments[0].end_frame), (1, 7));
+        assert_eq!(moments[0].path.len(), 4);
+        assert_eq!(moments[0].blob_history.len(), 4);
+    }
+
+    #[test]
+    fn moment_ends_at_last_sighting_after_coast_window() {
+        let config = tracker_config(TrackAssignment::Hungarian);
+        let coast_frames = 30;
+        let mut scene = SceneManager::new();
+        for _ in 0..3 {
+            scene.update(detect(&[(10, 20, 2, 2)]), &config);
+        }
+        for _ in 0..coast_frames {
+            let (_, completed) = scene.update(Vec::new(), &config);
+            assert!(completed.is_empty());
+        }
+
+        let (_, completed) = scene.update(Vec::new(), &config);
+        assert_eq!(completed.len(), 1);
+        assert_eq!((completed[0].start_frame, completed[0].end_frame), (1, 3));
+        assert_eq!(completed[0].path.len(), 3);
+        assert!(scene.get_active_moments().is_empty());
+        assert!(scene.get_tracked_blobs().is_empty());
+    }
+
+    #[test]
+    fn split_links_returned_and_stored_moments() {
+        let config = tracker_config(TrackAssignment::Hungarian);
+        let mut scene = SceneManager::new();
+        for _ in 0..2 {
+            scene.update(detect(&[(10, 20, 8, 4)]), &config);
+        }
+
+        // The group separates into two pieces three chunks apart.
+        let (started, completed) = scene.update(detect(&[(10, 20, 2, 4), (15, 20, 3, 4)]), &config);
+        assert!(completed.is_empty());
+        assert_eq!(started.len(), 1);
+        let child_id = started[0].id;
+        assert_eq!(child_id, 1);
+        assert_eq!(started[0].parent_ids, vec![0]);
+
+        let active = scene.get_active_moments();
+        assert_eq!(moment(active, 0).child_ids, vec![child_id]);
+        assert!(moment(active, 0).parent_ids.is_empty());
+        assert_eq!(moment(active, child_id).parent_ids, vec![0]);
+    }
+
+    #[test]
+    fn merge_links_returned_and_stored_moments() {
+        let config = tracker_config(TrackAssignment::Hungarian);
+        let mut scene = SceneManager::new();
+        for _ in 0..2 {
+            scene.update(detect(&[(10, 20, 3, 4), (16, 20, 3, 4)]), &config);
+        }
+
+        // The two objects come together into one detection; one track absorbs the other.
+        let (started, completed) = scene.update(detect(&[(10, 20, 9, 4)]), &config);
+        assert!(started.is_empty());
+        assert_eq!(completed.len(), 1);
+        let absorbed_id = completed[0].id;
+        let survivor_id = 1 - absorbed_id;
+        assert_eq!(completed[0].child_ids, vec![survivor_id]);
+        assert!(!completed[0].is_active);
+
+        assert_eq!(moment(scene.get_completed_moments(), absorbed_id).child_ids, vec![survivor_id]);
+        assert_eq!(moment(scene.get_active_moments(), survivor_id).parent_ids, vec![absorbed_id]);
+    }
+
+    #[test]
+    fn new_neighbor_is_not_a_split() {
+        let config = tracker_config(TrackAssignment::Hungarian);
+        let mut scene = SceneManager::new();
+        for _ in 0..2 {
+            scene.update(detect(&[(10, 20, 4, 4)]), &config);
+        }
+
+        // A larger object appears diagonally next to the track, within its predicted
+        // extent, while the tracked object keeps its size.
+        let blobs = detect(&[(10, 20, 4, 4), (14, 24, 30, 30)]);
+        assert_eq!(blobs.len(), 2);
+        let (started, _) = scene.update(blobs, &config);
+        assert_eq!(started.len(), 1);
+        assert!(started[0].parent_ids.is_empty());
+        assert!(moment(scene.get_active_moments(), 0).child_ids.is_empty());
+        assert!(scene.get_lineage_events().is_empty());
+        assert!(scene.get_tracked_blobs().iter().all(|track| track.parent_id.is_none()));
+    }
+}
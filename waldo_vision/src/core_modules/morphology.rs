@@ -0,0 +1,293 @@
+// THEORY:
+// The `morphology` module cleans up the binary picture of "where is something
+// happening" before the Spatial Grouping Layer turns it into blobs. Real status maps
+// are noisy: a single flickering chunk becomes a one-chunk blob, and a one-chunk gap
+// in the middle of a walking person cuts them into fragments that the tracker then
+// has to stitch back together.
+//
+// Key architectural principles:
+// 1.  **Chunk-Level Binary Morphology**: It operates on a `Vec<bool>` mask with one
+//     entry per chunk, derived from the status map. The classic operators are
+//     provided: erosion, dilation, opening (removes specks), closing (bridges gaps)
+//     and hole filling.
+// 2.  **Configurable Recipe**: The cleanup is a user-defined sequence of operations
+//     with a chosen structuring element, applied in order. An empty sequence leaves
+//     the mask untouched, so the stage costs nothing unless it is asked for.
+// 3.  **Stateless Utility**: Like the `BlobDetector`, it has no memory between frames.
+
+use crate::core_modules::smart_chunk::ChunkStatus;
+
+/// The neighborhood shape used by erosion and dilation, with its radius in chunks.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum StructuringElement {
+    /// A plus shape: chunks within the given Manhattan distance.
+    Cross(u32),
+    /// A square: chunks within the given Chebyshev distance.
+    Square(u32),
+    /// A disk: chunks within the given Euclidean distance.
+    Disk(u32),
+}
+
+impl Default for StructuringElement {
+    fn default() -> Self {
+        StructuringElement::Cross(1)
+    }
+}
+
+impl StructuringElement {
+    pub fn radius(&self) -> u32 {
+        match self {
+            StructuringElement::Cross(r) | StructuringElement::Square(r) | StructuringElement::Disk(r) => *r,
+        }
+    }
+
+    /// Returns the (dx, dy) offsets covered by this element, including the origin.
+    pub fn offsets(&self) -> Vec<(i32, i32)> {
+        let radius = self.radius() as i32;
+        let mut offsets = Vec::new();
+        for dy in -radius..=radius {
+            for dx in -radius..=radius {
+                let inside = match self {
+                    StructuringElement::Cross(_) => dx.abs() + dy.abs() <= radius,
+                    StructuringElement::Square(_) => true,
+                    StructuringElement::Disk(_) => dx * dx + dy * dy <= radius * radius,
+                };
+                if inside {
+                    offsets.push((dx, dy));
+                }
+            }
+        }
+        offsets
+    }
+}
+
+/// A single cleanup step applied to the chunk mask.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum MorphologyOperation {
+    Erode,
+    Dilate,
+    /// Erosion followed by dilation. Removes isolated chunks and thin protrusions.
+    Open,
+    /// Dilation followed by erosion. Bridges small gaps inside objects.
+    Close,
+    /// Sets every background region that does not touch the grid border.
+    FillHoles,
+}
+
+/// The recipe for cleaning the chunk mask before blob detection.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct MorphologyConfig {
+    /// The operations to apply, in order. Empty disables the stage.
+    pub operations: Vec<MorphologyOperation>,
+    /// The structuring element used by every erosion and dilation.
+    pub element: StructuringElement,
+}
+
+/// Builds the binary mask of chunks that are currently in an anomalous state.
+pub fn anomaly_mask(status_map: &[ChunkStatus]) -> Vec<bool> {
+    status_map
+        .iter()
+        .map(|status| matches!(status, ChunkStatus::AnomalousEvent(_)))
+        .collect()
+}
+
+/// Applies the configured sequence of operations to a chunk mask.
+pub fn apply(mask: &[bool], grid_width: u32, grid_height: u32, config: &MorphologyConfig) -> Vec<bool> {
+    let mut result = mask.to_vec();
+    for operation in &config.operations {
+        result = match operation {
+            MorphologyOperation::Erode => erode(&result, grid_width, grid_height, &config.element),
+            MorphologyOperation::Dilate => dilate(&result, grid_width, grid_height, &config.element),
+            MorphologyOperation::Open => open(&result, grid_width, grid_height, &config.element),
+            MorphologyOperation::Close => close(&result, grid_width, grid_height, &config.element),
+            MorphologyOperation::FillHoles => fill_holes(&result, grid_width, grid_height),
+        };
+    }
+    result
+}
+
+/// Keeps a chunk only if every in-grid chunk under the element is set.
+pub fn erode(mask: &[bool], grid_width: u32, grid_height: u32, element: &StructuringElement) -> Vec<bool> {
+    transform(mask, grid_width, grid_height, element, true)
+}
+
+/// Sets a chunk if any in-grid chunk under the element is set.
+pub fn dilate(mask: &[bool], grid_width: u32, grid_height: u32, element: &StructuringElement) -> Vec<bool> {
+    transform(mask, grid_width, grid_height, element, false)
+}
+
+pub fn open(mask: &[bool], grid_width: u32, grid_height: u32, element: &StructuringElement) -> Vec<bool> {
+    let eroded = erode(mask, grid_width, grid_height, element);
+    dilate(&eroded, grid_width, grid_height, element)
+}
+
+/// Dilation followed by erosion, computed on a grid padded by the element's radius so
+/// that growth toward the border is undone by the erosion instead of sticking to it.
+pub fn close(mask: &[bool], grid_width: u32, grid_height: u32, element: &StructuringElement) -> Vec<bool> {
+    let pad = element.radius();
+    let padded_width = grid_width + 2 * pad;
+    let padded_height = grid_height + 2 * pad;
+    let mut padded = vec![false; (padded_width * padded_height) as usize];
+    for y in 0..grid_height {
+        for x in 0..grid_width {
+            padded[((y + pad) * padded_width + x + pad) as usize] = mask[(y * grid_width + x) as usize];
+        }
+    }
+
+    let dilated = dilate(&padded, padded_width, padded_height, element);
+    let closed = erode(&dilated, padded_width, padded_height, element);
+
+    let mut result = Vec::with_capacity(mask.len());
+    for y in 0..grid_height {
+        for x in 0..grid_width {
+            result.push(closed[((y + pad) * padded_width + x + pad) as usize]);
+        }
+    }
+    result
+}
+
+/// Fills every enclosed background region by flooding the background from the border.
+pub fn fill_holes(mask: &[bool], grid_width: u32, grid_height: u32) -> Vec<bool> {
+    let width = grid_width as i32;
+    let height = grid_height as i32;
+    let mut outside = vec![false; mask.len()];
+    let mut stack: Vec<(i32, i32)> = Vec::new();
+
+    for y in 0..height {
+        for x in 0..width {
+            let on_border = x == 0 || y == 0 || x == width - 1 || y == height - 1;
+            let index = (y * width + x) as usize;
+            if on_border && !mask[index] {
+                outside[index] = true;
+                stack.push((x, y));
+            }
+        }
+    }
+
+    while let Some((x, y)) = stack.pop() {
+        for (dx, dy) in &[(0, 1), (0, -1), (1, 0), (-1, 0)] {
+            let nx = x + dx;
+            let ny = y + dy;
+            if nx < 0 || nx >= width || ny < 0 || ny >= height {
+                continue;
+            }
+            let index = (ny * width + nx) as usize;
+            if !mask[index] && !outside[index] {
+                outside[index] = true;
+                stack.push((nx, ny));
+            }
+        }
+    }
+
+    outside.iter().map(|is_outside| !is_outside).collect()
+}
+
+/// Combines the mask values under the element, centered on every chunk. With
+/// `require_all` the chunk survives only if all of them are set (erosion), otherwise
+/// if any of them is set (dilation). Offsets that fall outside the grid are ignored.
+fn transform(mask: &[bool], grid_width: u32, grid_height: u32, element: &StructuringElement, require_all: bool) -> Vec<bool> {
+    let offsets = element.offsets();
+    let width = grid_width as i32;
+    let height = grid_height as i32;
+    let mut result = vec![false; mask.len()];
+
+    for y in 0..height {
+        for x in 0..width {
+            let mut neighbors = offsets.iter().filter_map(|(dx, dy)| {
+                let nx = x + dx;
+                let ny = y + dy;
+                if nx < 0 || nx >= width || ny < 0 || ny >= height {
+                    None
+                } else {
+                    Some(mask[(ny * width + nx) as usize])
+                }
+            });
+            result[(y * width + x) as usize] = if require_all {
+                neighbors.all(|set| set)
+            } else {
+                neighbors.any(|set| set)
+            };
+        }
+    }
+    result
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Builds a mask from rows of text, where `#` is a set chunk.
+    fn mask_of(rows: &[&str]) -> (Vec<bool>, u32, u32) {
+        let mask = rows.iter().flat_map(|row| row.chars()).map(|c| c == '#').collect();
+        (mask, rows[0].len() as u32, rows.len() as u32)
+    }
+
+    /// Runs a morphology operation on the rows and renders the result the same way.
+    fn run(
+        rows: &[&str],
+        operation: fn(&[bool], u32, u32, &StructuringElement) -> Vec<bool>,
+        element: StructuringElement,
+    ) -> Vec<String> {
+        let (mask, width, height) = mask_of(rows);
+        operation(&mask, width, height, &element)
+            .chunks(width as usize)
+            .map(|row| row.iter().map(|set| if *set { '#' } else { '.' }).collect())
+            .collect()
+    }
+
+    #[test]
+    fn element_shapes() {
+        assert_eq!(StructuringElement::Cross(1).offsets().len(), 5);
+        assert_eq!(StructuringElement::Square(1).offsets().len(), 9);
+        assert_eq!(StructuringElement::Square(2).offsets().len(), 25);
+        assert!(StructuringElement::Disk(3).offsets().contains(&(2, 2)));
+        assert!(!StructuringElement::Disk(3).offsets().contains(&(3, 1)));
+        assert!(!StructuringElement::Cross(3).offsets().contains(&(2, 2)));
+    }
+
+    #[test]
+    fn erosion_shrinks_and_ignores_the_border() {
+        let block = [".....", ".###.", ".###.", ".###.", "....."];
+        assert_eq!(run(&block, erode, StructuringElement::Cross(1)), [".....", ".....", "..#..", ".....", "....."]);
+
+        // Chunks outside the grid do not count as background.
+        let corner = ["###..", "###..", "###..", ".....", "....."];
+        assert_eq!(run(&corner, erode, StructuringElement::Cross(1)), ["##...", "##...", ".....", ".....", "....."]);
+        let full = ["###", "###"];
+        assert_eq!(run(&full, erode, StructuringElement::Square(1)), full);
+    }
+
+    #[test]
+    fn dilation_grows_by_the_element() {
+        let dot = [".....", ".....", "..#..", ".....", "....."];
+        assert_eq!(run(&dot, dilate, StructuringElement::Cross(1)), [".....", "..#..", ".###.", "..#..", "....."]);
+        assert_eq!(run(&dot, dilate, StructuringElement::Square(1)), [".....", ".###.", ".###.", ".###.", "....."]);
+
+        let corner = ["#...", "....", "...."];
+        assert_eq!(run(&corner, dilate, StructuringElement::Square(1)), ["##..", "##..", "...."]);
+    }
+
+    #[test]
+    fn opening_removes_specks_and_keeps_objects() {
+        let rows = ["#......", "....###", "....###", "....###"];
+        assert_eq!(run(&rows, open, StructuringElement::Square(1)), [".......", "....###", "....###", "....###"]);
+    }
+
+    #[test]
+    fn closing_bridges_gaps_without_sticking_to_the_border() {
+        let rows = [".......", ".##.##.", ".##.##.", ".##.##.", "......."];
+        let closed = run(&rows, close, StructuringElement::Cross(1));
+        assert_eq!(closed, [".......", ".##.##.", ".#####.", ".##.##.", "......."]);
+
+        // Growth into the padding beyond the border is eroded away again.
+        let corner = ["##...", "##...", "....."];
+        assert_eq!(run(&corner, close, StructuringElement::Square(1)), corner);
+    }
+
+    #[test]
+    fn holes_are_filled_but_open_bays_are_not() {
+        let (mask, width, height) = mask_of(&["#####", "#..#.", "#####", "#...."]);
+        let (expected, _, _) = mask_of(&["#####", "####.", "#####", "#...."]);
+        assert_eq!(fill_holes(&mask, width, height), expected);
+    }
+}
@@ -0,0 +1,198 @@
+// THEORY:
+// The `perspective` module corrects blob sizes for distance from the camera. On a
+// camera looking down at a ground plane, a person near the bottom of the frame covers
+// many chunks and the same person near the horizon covers only a few. Raw chunk counts
+// therefore say more about where an object is than about what it is.
+//
+// Key architectural principles:
+// 1.  **Per-Row Scale**: The model stores, for each grid row, the size in chunks of a
+//     reference object whose base (the bottom of its bounding box, where it meets the
+//     ground) sits on that row. A blob's normalized size is its chunk count divided by
+//     that reference size, so the reference object measures 1.0 anywhere in the frame.
+// 2.  **Calibration From Examples**: Rather than asking for camera parameters, the
+//     model is fitted from a few observed sizes of the reference object at different
+//     rows. For a flat ground plane the apparent height of an object grows linearly
+//     with its distance below the horizon, so the square root of its area is fitted
+//     as a straight line over the rows.
+// 3.  **Neutral Default**: The `Uniform` model has a reference size of one chunk on
+//     every row, so normalized sizes equal chunk counts and nothing changes unless a
+//     model is configured.
+
+use crate::core_modules::smart_blob::SmartBlob;
+use std::fmt;
+
+/// The smallest reference size, in chunks, the calibration will assign to a row.
+/// Rows at or above the fitted horizon would otherwise get a zero or negative size.
+const MIN_REFERENCE_SIZE: f64 = 0.25;
+
+/// An observed size of the reference object, used for calibration.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct ReferenceSize {
+    /// The grid row of the object's base (the bottom row of its bounding box).
+    pub row: u32,
+    /// The object's size in chunks at that row.
+    pub size_in_chunks: f64,
+}
+
+/// Errors that can occur when calibrating a perspective model.
+#[derive(Debug, Clone, PartialEq)]
+pub enum PerspectiveError {
+    /// Fewer than two reference sizes were given.
+    NotEnoughReferences,
+    /// The references all lie on the same row, or their sizes are not positive.
+    DegenerateReferences,
+}
+
+impl fmt::Display for PerspectiveError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            PerspectiveError::NotEnoughReferences => {
+                write!(f, "at least two reference sizes are needed to calibrate perspective")
+            }
+            PerspectiveError::DegenerateReferences => {
+                write!(f, "reference sizes must be positive and span at least two rows")
+            }
+        }
+    }
+}
+
+impl std::error::Error for PerspectiveError {}
+
+/// Maps a blob's position to the size a reference object would have there.
+#[derive(Debug, Clone, PartialEq, Default)]
+pub enum PerspectiveModel {
+    /// No perspective correction: the reference size is one chunk everywhere.
+    #[default]
+    Uniform,
+    /// The reference size in chunks for each grid row, top to bottom. Rows past the
+    /// end of the table use its last entry.
+    RowScale(Vec<f64>),
+}
+
+impl PerspectiveModel {
+    /// Fits a `RowScale` model for a grid of the given height from observed sizes of the
+    /// reference object, assuming a flat ground plane.
+    pub fn from_reference_sizes(references: &[ReferenceSize], grid_height: u32) -> Result<Self, PerspectiveError> {
+        if references.len() < 2 {
+            return Err(PerspectiveError::NotEnoughReferences);
+        }
+        if references.iter().any(|r| r.size_in_chunks <= 0.0) {
+            return Err(PerspectiveError::DegenerateReferences);
+        }
+
+        // Least-squares line through (row, sqrt(size)).
+        let count = references.len() as f64;
+        let mean_row = references.iter().map(|r| r.row as f64).sum::<f64>() / count;
+        let mean_linear = references.iter().map(|r| r.size_in_chunks.sqrt()).sum::<f64>() / count;
+        let covariance: f64 = references
+            .iter()
+            .map(|r| (r.row as f64 - mean_row) * (r.size_in_chunks.sqrt() - mean_linear))
+            .sum();
+        let variance: f64 = references.iter().map(|r| (r.row as f64 - mean_row).powi(2)).sum();
+        if variance == 0.0 {
+            return Err(PerspectiveError::DegenerateReferences);
+        }
+        let slope = covariance / variance;
+        let intercept = mean_linear - slope * mean_row;
+
+        let rows = (0..grid_height)
+            .map(|row| {
+                let linear = (slope * row as f64 + intercept).max(0.0);
+                (linear * linear).max(MIN_REFERENCE_SIZE)
+            })
+            .collect();
+        Ok(PerspectiveModel::RowScale(rows))
+    }
+
+    /// The size in chunks of the reference object with its base on the given row.
+    pub fn reference_size_at(&self, row: u32) -> f64 {
+        match self {
+            PerspectiveModel::Uniform => 1.0,
+            PerspectiveModel::RowScale(rows) => rows
+                .get(row as usize)
+                .or(rows.last())
+                .copied()
+                .unwrap_or(1.0)
+                .max(f64::EPSILON),
+        }
+    }
+
+    /// The blob's size relative to the reference object at the blob's base.
+    pub fn normalized_size(&self, blob: &SmartBlob) -> f64 {
+        let (_, bottom_right) = blob.bounding_box;
+        blob.size_in_chunks as f64 / self.reference_size_at(bottom_right.y)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::core_modules::tracker::tests::blob_of;
+
+    /// The reference object covers 2x2 chunks with its base on row 10 and 6x6 on row
+    /// 30, so its linear size is `0.2 * row` and the horizon is row 0.
+    fn calibrated() -> PerspectiveModel {
+        let references = [
+            ReferenceSize { row: 10, size_in_chunks: 4.0 },
+            ReferenceSize { row: 30, size_in_chunks: 36.0 },
+        ];
+        PerspectiveModel::from_reference_sizes(&references, 40).unwrap()
+    }
+
+    fn assert_close(actual: f64, expected: f64) {
+        assert!((actual - expected).abs() < 1e-9, "{actual} != {expected}");
+    }
+
+    #[test]
+    fn calibration_follows_the_ground_plane() {
+        let model = calibrated();
+        assert_close(model.reference_size_at(10), 4.0);
+        assert_close(model.reference_size_at(20), 16.0);
+        assert_close(model.reference_size_at(30), 36.0);
+        assert_close(model.reference_size_at(5), 1.0);
+        // At the horizon the size is clamped instead of reaching zero.
+        assert_close(model.reference_size_at(0), MIN_REFERENCE_SIZE);
+        // Rows past the table reuse its last entry.
+        assert_close(model.reference_size_at(100), model.reference_size_at(39));
+    }
+
+    #[test]
+    fn reference_object_measures_one_anywhere() {
+        let model = calibrated();
+        let far = blob_of(&[(20, 9, 2, 2)]);
+        let near = blob_of(&[(20, 17, 4, 4)]);
+        assert_close(model.normalized_size(&far), 1.0);
+        assert_close(model.normalized_size(&near), 1.0);
+
+        // The near-sized object far away is four reference objects.
+        let large_far = blob_of(&[(20, 7, 4, 4)]);
+        assert_close(model.normalized_size(&large_far), 4.0);
+
+        assert_close(PerspectiveModel::Uniform.normalized_size(&near), 16.0);
+    }
+
+    #[test]
+    fn degenerate_references_are_rejected() {
+        let at = |row, size_in_chunks| ReferenceSize { row, size_in_chunks };
+        assert_eq!(
+            PerspectiveModel::from_reference_sizes(&[], 40),
+            Err(PerspectiveError::NotEnoughReferences)
+        );
+        assert_eq!(
+            PerspectiveModel::from_reference_sizes(&[at(10, 4.0)], 40),
+            Err(PerspectiveError::NotEnoughReferences)
+        );
+        assert_eq!(
+            PerspectiveModel::from_reference_sizes(&[at(10, 4.0), at(10, 9.0)], 40),
+            Err(PerspectiveError::DegenerateReferences)
+        );
+        assert_eq!(
+            PerspectiveModel::from_reference_sizes(&[at(10, 4.0), at(30, 0.0)], 40),
+            Err(PerspectiveError::DegenerateReferences)
+        );
+        assert_eq!(
+            PerspectiveModel::from_reference_sizes(&[at(10, -4.0), at(30, 36.0)], 40),
+            Err(PerspectiveError::DegenerateReferences)
+        );
+    }
+}
@@ -13,16 +13,238 @@
 //
 // This module intentionally separates the concept of "what a pixel is" from the more
 // complex question of "how a pixel relates to others," which is handled by `SmartPixel`.
+//
+// Luminance is defined here, and only here, through a `LuminanceModel`. The model selects
+// the RGB weights of a broadcast standard (BT.601, BT.709, BT.2020) and can optionally
+// decode the sRGB transfer curve first, so that brightness deltas are measured in linear
+// light instead of being compressed in dark regions and stretched in bright ones. The
+// decoding uses a precomputed lookup table, keeping the per-pixel cost to a single index.
+//
+// High bit-depth sources (thermal and HDR cameras) are represented by `Pixel16`, which
+// keeps the full 16-bit container. Both pixel types implement `PixelData`, the small
+// trait that lets the chunk summarization layer stay generic over channel depth. The
+// `BitDepth` of the data travels alongside it, so that linearization and any thresholds
+// expressed on the 8-bit scale can be rescaled to the source's real value range.
 
 pub mod pixel {
+    use std::sync::OnceLock;
+
     pub type Byte = u8;
     pub type Bytes = Vec<Byte>;
     pub type Channel = Byte;
     pub type Luminance = f64;
     pub type Color = i16;
     pub type Sum = f32;
+    pub type Channel16 = u16;
 
     const CHANNELS: usize = 4;
+    const CHANNEL_MAX: f64 = 255.0;
+
+    /// The number of significant bits per channel in the source data.
+    #[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+    pub enum BitDepth {
+        #[default]
+        Eight,
+        Ten,
+        Twelve,
+        /// Typical of radiometric thermal cameras using a 16-bit container.
+        Fourteen,
+        Sixteen,
+    }
+
+    impl BitDepth {
+        pub fn bits(&self) -> u32 {
+            match self {
+                BitDepth::Eight => 8,
+                BitDepth::Ten => 10,
+                BitDepth::Twelve => 12,
+                BitDepth::Fourteen => 14,
+                BitDepth::Sixteen => 16,
+            }
+        }
+
+        /// The largest channel value representable at this depth.
+        pub fn max_value(&self) -> u32 {
+            (1 << self.bits()) - 1
+        }
+
+        /// The factor that converts a threshold on the 8-bit scale to this depth.
+        pub fn threshold_scale(&self) -> f64 {
+            self.max_value() as f64 / CHANNEL_MAX
+        }
+    }
+
+    /// The common interface of all pixel containers, regardless of channel depth.
+    /// Channels are exposed widened to `u32` so summaries can be computed generically.
+    pub trait PixelData: Clone + Default {
+        /// Returns the (red, green, blue, alpha) channel values.
+        fn channels(&self) -> [u32; 4];
+        /// Builds a pixel from (red, green, blue, alpha) values already in range.
+        fn from_channels(channels: [u32; 4]) -> Self;
+
+        /// The sum of the RGB channels.
+        fn channel_sum(&self) -> Sum {
+            let [red, green, blue, _] = self.channels();
+            (red + green + blue) as Sum
+        }
+
+        /// The share of each RGB channel in the channel sum.
+        fn channel_ratios(&self) -> (f32, f32, f32) {
+            let [red, green, blue, _] = self.channels();
+            let sum = self.channel_sum();
+            if sum == 0.0 {
+                return (0.0, 0.0, 0.0);
+            }
+            (red as f32 / sum, green as f32 / sum, blue as f32 / sum)
+        }
+    }
+
+    /// The broadcast standard whose RGB weights are used to compute luminance.
+    #[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+    pub enum LuminanceStandard {
+        /// SD video weights (0.299, 0.587, 0.114). The historical default.
+        #[default]
+        Bt601,
+        /// HD video weights (0.2126, 0.7152, 0.0722).
+        Bt709,
+        /// UHD / HDR video weights (0.2627, 0.6780, 0.0593).
+        Bt2020,
+    }
+
+    impl LuminanceStandard {
+        /// Returns the (red, green, blue) weights of this standard.
+        pub fn coefficients(&self) -> (f64, f64, f64) {
+            match self {
+                LuminanceStandard::Bt601 => (0.299, 0.587, 0.114),
+                LuminanceStandard::Bt709 => (0.2126, 0.7152, 0.0722),
+                LuminanceStandard::Bt2020 => (0.2627, 0.6780, 0.0593),
+            }
+        }
+    }
+
+    /// Describes how luminance is derived from a pixel's channels.
+    #[derive(Debug, Clone, Copy, PartialEq, Default)]
+    pub struct LuminanceModel {
+        /// The RGB weighting standard.
+        pub standard: LuminanceStandard,
+        /// If true, channels are decoded from sRGB to linear light before weighting.
+        /// Results stay on the 0-255 scale so thresholds remain comparable.
+        pub linear_light: bool,
+    }
+
+    impl LuminanceModel {
+        pub fn new(standard: LuminanceStandard, linear_light: bool) -> Self {
+            Self {
+                standard,
+                linear_light,
+            }
+        }
+
+        /// Maps a raw channel value into the space in which this model operates.
+        pub fn channel_value(&self, channel: Channel) -> f64 {
+            if self.linear_light {
+                srgb_to_linear(channel)
+            } else {
+                channel as f64
+            }
+        }
+
+        /// Maps a raw channel value of the given depth into the space in which this
+        /// model operates. Linear-light results stay on the depth's own value range.
+        pub fn channel_value_at(&self, value: u32, depth: BitDepth) -> f64 {
+            if !self.linear_light {
+                return value as f64;
+            }
+            if depth == BitDepth::Eight {
+                return srgb_to_linear(value.min(255) as Channel);
+            }
+            srgb_to_linear_at(value, depth)
+        }
+
+        /// The inverse of `channel_value_at`: encodes a value from this model's space
+        /// back into a raw channel value of the given depth.
+        pub fn encode_at(&self, value: f64, depth: BitDepth) -> u32 {
+            let max = depth.max_value() as f64;
+            let encoded = if self.linear_light {
+                encode_srgb((value / max).clamp(0.0, 1.0)) * max
+            } else {
+                value
+            };
+            encoded.round().clamp(0.0, max) as u32
+        }
+
+        /// Calculates the luminance of a pixel according to this model.
+        pub fn luminance(&self, pixel: &Pixel) -> Luminance {
+            self.luminance_at(pixel, BitDepth::Eight)
+        }
+
+        /// Calculates the luminance of a pixel of any depth according to this model.
+        /// The result is on the depth's own value range.
+        pub fn luminance_at<P: PixelData>(&self, pixel: &P, depth: BitDepth) -> Luminance {
+            let (kr, kg, kb) = self.standard.coefficients();
+            let [red, green, blue, _] = pixel.channels();
+            kr * self.channel_value_at(red, depth)
+                + kg * self.channel_value_at(green, depth)
+                + kb * self.channel_value_at(blue, depth)
+        }
+    }
+
+    /// The sRGB decoding curve on normalized (0-1) values.
+    fn decode_srgb(encoded: f64) -> f64 {
+        if encoded <= 0.04045 {
+            encoded / 12.92
+        } else {
+            ((encoded + 0.055) / 1.055).powf(2.4)
+        }
+    }
+
+    /// The sRGB encoding curve on normalized (0-1) values.
+    fn encode_srgb(linear: f64) -> f64 {
+        if linear <= 0.0031308 {
+            linear * 12.92
+        } else {
+            1.055 * linear.powf(1.0 / 2.4) - 0.055
+        }
+    }
+
+    /// Decodes an sRGB-encoded channel into linear light, on the 0-255 scale.
+    /// Backed by a lookup table that is built once on first use.
+    pub fn srgb_to_linear(channel: Channel) -> f64 {
+        static TABLE: OnceLock<[f64; 256]> = OnceLock::new();
+        let table = TABLE.get_or_init(|| {
+            let mut table = [0.0; 256];
+            for (value, entry) in table.iter_mut().enumerate() {
+                *entry = decode_srgb(value as f64 / CHANNEL_MAX) * CHANNEL_MAX;
+            }
+            table
+        });
+        table[channel as usize]
+    }
+
+    /// Decodes an sRGB-encoded channel of the given depth into linear light, on the
+    /// same scale. Values above the depth's maximum are clamped. Backed by one lookup
+    /// table per depth (up to 65536 entries), each built once on first use.
+    pub fn srgb_to_linear_at(value: u32, depth: BitDepth) -> f64 {
+        static TABLES: [OnceLock<Vec<f64>>; 5] = [const { OnceLock::new() }; 5];
+        let index = match depth {
+            BitDepth::Eight => 0,
+            BitDepth::Ten => 1,
+            BitDepth::Twelve => 2,
+            BitDepth::Fourteen => 3,
+            BitDepth::Sixteen => 4,
+        };
+        let max = depth.max_value();
+        let table = TABLES[index].get_or_init(|| {
+            (0..=max).map(|value| decode_srgb(value as f64 / max as f64) * max as f64).collect()
+        });
+        table[value.min(max) as usize]
+    }
+
+    /// Encodes a linear-light value on the 0-255 scale back into an sRGB channel.
+    pub fn linear_to_srgb(value: f64) -> Channel {
+        let encoded = encode_srgb((value / CHANNEL_MAX).clamp(0.0, 1.0));
+        (encoded * CHANNEL_MAX).round() as Channel
+    }
 
     /// A "dumb" data container representing a single RGBA pixel.
     #[derive(Debug, Clone, PartialEq)]
@@ -58,14 +280,42 @@ pub mod pixel {
             }
         }
 
+        /// Calculates luminance with the default model (BT.601 weights on gamma-encoded values).
         pub fn luminance(&self) -> Luminance {
-            0.299 * self.red as f64 + 0.587 * self.green as f64 + 0.114 * self.blue as f64
+            self.luminance_with(&LuminanceModel::default())
+        }
+
+        /// Calculates luminance with an explicit standard and transfer model.
+        pub fn luminance_with(&self, model: &LuminanceModel) -> Luminance {
+            model.luminance(self)
         }
 
         pub fn sum(&self) -> Sum {
             (self.red as Color + self.green as Color + self.blue as Color) as Sum
         }
 
+        /// Converts the pixel to hue (degrees, 0-360), saturation (0-1) and value (0-1).
+        pub fn hsv(&self) -> (f32, f32, f32) {
+            let red = self.red as f32 / 255.0;
+            let green = self.green as f32 / 255.0;
+            let blue = self.blue as f32 / 255.0;
+            let max = red.max(green).max(blue);
+            let min = red.min(green).min(blue);
+            let delta = max - min;
+
+            let hue = if delta == 0.0 {
+                0.0
+            } else if max == red {
+                60.0 * ((green - blue) / delta).rem_euclid(6.0)
+            } else if max == green {
+                60.0 * ((blue - red) / delta + 2.0)
+            } else {
+                60.0 * ((red - green) / delta + 4.0)
+            };
+            let saturation = if max == 0.0 { 0.0 } else { delta / max };
+            (hue, saturation, max)
+        }
+
         pub fn color_ratios(&self) -> (f32, f32, f32) {
             let sum = self.sum();
             if sum == 0.0 {
@@ -93,4 +343,231 @@ pub mod pixel {
             vec![pixel.red, pixel.green, pixel.blue, pixel.alpha]
         }
     }
+
+    impl PixelData for Pixel {
+        fn channels(&self) -> [u32; 4] {
+            [
+                self.red as u32,
+                self.green as u32,
+                self.blue as u32,
+                self.alpha as u32,
+            ]
+        }
+
+        fn from_channels(channels: [u32; 4]) -> Self {
+            Pixel::new(
+                channels[0] as Channel,
+                channels[1] as Channel,
+                channels[2] as Channel,
+                channels[3] as Channel,
+            )
+        }
+    }
+
+    /// A "dumb" data container representing a single RGBA pixel with 16-bit channels.
+    /// The meaningful range of each channel depends on the source's `BitDepth`.
+    #[derive(Debug, Clone, PartialEq, Default)]
+    pub struct Pixel16 {
+        /// The red channel value.
+        pub red: Channel16,
+        /// The green channel value.
+        pub green: Channel16,
+        /// The blue channel value.
+        pub blue: Channel16,
+        /// The alpha (transparency) channel value.
+        pub alpha: Channel16,
+    }
+
+    impl Pixel16 {
+        pub fn new(red: Channel16, green: Channel16, blue: Channel16, alpha: Channel16) -> Self {
+            Pixel16 {
+                red,
+                green,
+                blue,
+                alpha,
+            }
+        }
+
+        /// Creates an opaque pixel from a single intensity value, as produced by
+        /// grayscale and thermal sensors.
+        pub fn gray(value: Channel16, depth: BitDepth) -> Self {
+            Pixel16::new(value, value, value, depth.max_value() as Channel16)
+        }
+
+        /// Calculates luminance on the value range of the given depth.
+        pub fn luminance_with(&self, model: &LuminanceModel, depth: BitDepth) -> Luminance {
+            model.luminance_at(self, depth)
+        }
+
+        pub fn sum(&self) -> Sum {
+            self.channel_sum()
+        }
+
+        pub fn color_ratios(&self) -> (f32, f32, f32) {
+            self.channel_ratios()
+        }
+    }
+
+    impl PixelData for Pixel16 {
+        fn channels(&self) -> [u32; 4] {
+            [
+                self.red as u32,
+                self.green as u32,
+                self.blue as u32,
+                self.alpha as u32,
+            ]
+        }
+
+        fn from_channels(channels: [u32; 4]) -> Self {
+            Pixel16::new(
+                channels[0] as Channel16,
+                channels[1] as Channel16,
+                channels[2] as Channel16,
+                channels[3] as Channel16,
+            )
+        }
+    }
+
+    impl From<&[Channel16]> for Pixel16 {
+        fn from(channels: &[Channel16]) -> Self {
+            if channels.len() != CHANNELS {
+                panic!("Cannot convert {} channels into pixel.", channels.len());
+            }
+            Pixel16::new(channels[0], channels[1], channels[2], channels[3])
+        }
+    }
+
+    /// Carries an 8-bit pixel into the 16-bit container without rescaling its values.
+    impl From<&Pixel> for Pixel16 {
+        fn from(pixel: &Pixel) -> Self {
+            Pixel16::from_channels(pixel.channels())
+        }
+    }
+
+    #[cfg(test)]
+    mod tests {
+        use super::*;
+
+        const STANDARDS: [LuminanceStandard; 3] =
+            [LuminanceStandard::Bt601, LuminanceStandard::Bt709, LuminanceStandard::Bt2020];
+
+        fn assert_close(actual: f64, expected: f64, tolerance: f64) {
+            assert!((actual - expected).abs() <= tolerance, "{actual} != {expected}");
+        }
+
+        /// The sRGB decoding curve computed directly with `powf`, without the tables.
+        fn reference_decode(value: f64, max: f64) -> f64 {
+            let encoded = value / max;
+            let linear = if encoded <= 0.04045 { encoded / 12.92 } else { ((encoded + 0.055) / 1.055).powf(2.4) };
+            linear * max
+        }
+
+        #[test]
+        fn white_has_full_luminance_under_every_standard() {
+            let white = Pixel::new(255, 255, 255, 255);
+            for standard in STANDARDS {
+                for linear_light in [false, true] {
+                    let luminance = white.luminance_with(&LuminanceModel::new(standard, linear_light));
+                    assert_close(luminance / CHANNEL_MAX, 1.0, 1e-9);
+                }
+            }
+            assert_eq!(Pixel::new(0, 0, 0, 255).luminance(), 0.0);
+        }
+
+        #[test]
+        fn primaries_weigh_by_the_standard() {
+            let expected = [
+                (LuminanceStandard::Bt601, [0.299, 0.587, 0.114]),
+                (LuminanceStandard::Bt709, [0.2126, 0.7152, 0.0722]),
+                (LuminanceStandard::Bt2020, [0.2627, 0.6780, 0.0593]),
+            ];
+            let primaries = [Pixel::new(255, 0, 0, 255), Pixel::new(0, 255, 0, 255), Pixel::new(0, 0, 255, 255)];
+            for (standard, weights) in expected {
+                let model = LuminanceModel::new(standard, false);
+                for (primary, weight) in primaries.iter().zip(weights) {
+                    assert_close(model.luminance(primary), weight * CHANNEL_MAX, 1e-9);
+                }
+            }
+        }
+
+        #[test]
+        fn mid_gray_linearizes_to_its_known_value() {
+            // sRGB 128 is 21.59 % of white in linear light.
+            let gray = Pixel::new(128, 128, 128, 255);
+            let linear = gray.luminance_with(&LuminanceModel::new(LuminanceStandard::Bt709, true));
+            assert_close(linear / CHANNEL_MAX, 0.21586, 1e-5);
+            assert_close(gray.luminance(), 128.0, 1e-9);
+            // Dark values use the linear segment of the curve.
+            assert_close(srgb_to_linear(10), 10.0 / 12.92, 1e-9);
+        }
+
+        #[test]
+        fn lookup_tables_match_powf() {
+            for value in 0..=255u32 {
+                let expected = reference_decode(value as f64, CHANNEL_MAX);
+                assert_close(srgb_to_linear(value as Channel), expected, 1e-12);
+                assert_close(srgb_to_linear_at(value, BitDepth::Eight), expected, 1e-12);
+                assert_eq!(linear_to_srgb(expected), value as Channel);
+            }
+            for depth in [BitDepth::Ten, BitDepth::Twelve, BitDepth::Sixteen] {
+                let max = depth.max_value();
+                for value in (0..=max).step_by(7).chain([max]) {
+                    assert_close(srgb_to_linear_at(value, depth), reference_decode(value as f64, max as f64), 1e-9);
+                }
+                // Values beyond the depth are clamped to its maximum.
+                assert_close(srgb_to_linear_at(max + 1, depth), max as f64, 1e-9);
+            }
+        }
+
+        /// One third of full scale at each depth: 85 of 255, 341 of 1023, 1365 of 4095
+        /// and 21845 of 65535.
+        const THIRD: [(BitDepth, Channel16); 4] = [
+            (BitDepth::Eight, 85),
+            (BitDepth::Ten, 341),
+            (BitDepth::Twelve, 1365),
+            (BitDepth::Sixteen, 21845),
+        ];
+
+        #[test]
+        fn depths_scale_to_their_value_range() {
+            assert_eq!(BitDepth::Ten.max_value(), 1023);
+            assert_eq!(BitDepth::Twelve.max_value(), 4095);
+            assert_eq!(BitDepth::Fourteen.max_value(), 16383);
+            assert_eq!(BitDepth::Sixteen.max_value(), 65535);
+            assert_eq!(BitDepth::Eight.threshold_scale(), 1.0);
+            assert_close(BitDepth::Ten.threshold_scale(), 1023.0 / 255.0, 1e-12);
+            assert_close(BitDepth::Twelve.threshold_scale(), 4095.0 / 255.0, 1e-12);
+            assert_close(BitDepth::Sixteen.threshold_scale(), 257.0, 1e-12);
+        }
+
+        #[test]
+        fn same_value_at_every_depth_has_the_same_luminance() {
+            for linear_light in [false, true] {
+                let model = LuminanceModel::new(LuminanceStandard::Bt709, linear_light);
+                let eight_bit = model.luminance(&Pixel::new(85, 85, 85, 255));
+                for (depth, value) in THIRD {
+                    let luminance = Pixel16::gray(value, depth).luminance_with(&model, depth);
+                    assert_close(luminance / depth.threshold_scale(), eight_bit, 1e-9);
+                }
+            }
+        }
+
+        #[test]
+        fn eight_bit_pixels_carry_into_the_16_bit_container_unchanged() {
+            let pixel = Pixel::new(85, 170, 255, 255);
+            let widened = Pixel16::from(&pixel);
+            assert_eq!(widened, Pixel16::new(85, 170, 255, 255));
+            let model = LuminanceModel::new(LuminanceStandard::Bt601, true);
+            assert_close(model.luminance_at(&widened, BitDepth::Eight), model.luminance(&pixel), 1e-12);
+        }
+
+        #[test]
+        fn linear_values_encode_back_at_every_depth() {
+            let model = LuminanceModel::new(LuminanceStandard::Bt601, true);
+            for (depth, value) in THIRD {
+                let linear = model.channel_value_at(value as u32, depth);
+                assert_eq!(model.encode_at(linear, depth), value as u32);
+            }
+        }
+    }
 }
@@ -22,15 +22,67 @@
 //     final architectural layer (Behavioral Analysis), which will track these
 //     blobs over time to create "Moments" and narratives.
 
+use crate::core_modules::appearance::ColorHistogram;
+use crate::core_modules::blob_detector::blob_detector::describe_shape;
+use crate::core_modules::geometry::{FrameGeometry, NormalizedBox, NormalizedPoint, PixelBox, PixelPoint};
 use crate::core_modules::smart_chunk::AnomalyDetails;
 
 /// A simple struct to represent a 2D point or coordinate on the chunk grid.
+/// See the `geometry` module for the pixel and normalized coordinate spaces.
 #[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
 pub struct Point {
     pub x: u32,
     pub y: u32,
 }
 
+/// Geometric descriptors of a blob's shape, measured on the chunk grid.
+/// Each chunk is treated as a unit square centered on its grid coordinate.
+#[derive(Debug, Clone, PartialEq)]
+pub struct BlobShape {
+    /// Bounding box width divided by height. Tall objects (people) are below 1.0,
+    /// long objects (vehicles, animals) above it.
+    pub aspect_ratio: f64,
+    /// The number of chunk edges on the blob's outline, including the edges of holes.
+    pub perimeter: f64,
+    /// Isoperimetric ratio `4 * pi * area / perimeter^2`. Highest for round, solid blobs.
+    pub compactness: f64,
+    /// The fraction of the bounding box covered by the blob (fill ratio).
+    pub extent: f64,
+    /// The fraction of the convex hull of the blob's chunk squares covered by the blob.
+    /// 1.0 for convex blobs, lower for L-shapes, rings and other concave outlines.
+    pub solidity: f64,
+    /// The angle of the major axis from the grid's x axis, in radians (-pi/2 to pi/2).
+    /// The y axis points down, as in image coordinates.
+    pub orientation: f64,
+    /// The full length of the major axis of the ellipse with the same second-order moments.
+    pub major_axis: f64,
+    /// The full length of the minor axis of the ellipse with the same second-order moments.
+    pub minor_axis: f64,
+    /// The convex hull of the blob's chunk coordinates, starting at the top-left-most
+    /// point and running clockwise on screen (with the y axis pointing down).
+    pub convex_hull: Vec<Point>,
+}
+
+/// A pixel-accurate foreground mask for a blob. It covers the rectangle of pixels that
+/// was searched around the blob's chunks.
+#[derive(Debug, Clone, PartialEq)]
+pub struct ForegroundMask {
+    /// The top-left corner of the searched rectangle, in pixel coordinates.
+    pub origin: Point,
+    /// The width of the searched rectangle in pixels.
+    pub width: u32,
+    /// The height of the searched rectangle in pixels.
+    pub height: u32,
+    /// Row-major, `width * height` entries; `true` where the pixel differs from its
+    /// own luminance in the previous frame by more than the threshold.
+    pub mask: Vec<bool>,
+    /// The number of foreground pixels in the mask.
+    pub pixel_count: usize,
+    /// The tight box around the foreground pixels, in pixel coordinates (inclusive).
+    /// `None` when no pixel passed the threshold.
+    pub bounding_box: Option<(Point, Point)>,
+}
+
 /// Represents a single, spatially coherent object detected in a frame.
 /// This is a "dumb" data container that summarizes the properties of a detected motion event.
 #[derive(Debug, Clone)]
@@ -44,10 +96,163 @@ pub struct SmartBlob {
     pub chunk_coords: Vec<Point>,
     /// The total number of chunks in the blob, representing its area.
     pub size_in_chunks: usize,
-    /// The average "significance" scores (luminance, color, hue) from all chunks
+    /// The blob's size relative to a reference object at the same distance from the
+    /// camera, as given by the pipeline's perspective model. Equal to `size_in_chunks`
+    /// when no perspective model is configured.
+    pub normalized_size: f64,
+    /// How many of the blob's chunks are in an `AnomalousEvent` state.
+    pub anomalous_chunk_count: usize,
+    /// How many of the blob's chunks are in a `PredictableMotion` state. Non-zero only
+    /// when the detector is configured to include predictable motion.
+    pub predictable_chunk_count: usize,
+    /// The average "significance" scores (luminance, color, hue) from all anomalous chunks
     /// within the blob. This forms the core of the blob's analytical signature.
     pub average_anomaly: AnomalyDetails,
-    /// The center of the blob, weighted by the `luminance_score` of each chunk.
+    /// The center of the blob, weighted by the heat of each chunk (the `luminance_score`
+    /// for anomalous chunks).
     /// This provides a more precise location of the "epicenter" of the motion.
+    /// In grid units; see `pixel_center_of_mass` and `normalized_center_of_mass`.
     pub center_of_mass: (f64, f64),
+    /// Shape descriptors computed from the blob's chunks.
+    pub shape: BlobShape,
+    /// The color histogram of the frame pixels under the blob. Filled in by the
+    /// pipeline once the blob survives filtering; `None` straight out of the detector.
+    pub appearance: Option<ColorHistogram>,
+    /// The pixel-level foreground of the blob. Filled in by the pipeline only when
+    /// foreground refinement is enabled.
+    pub foreground: Option<ForegroundMask>,
+}
+
+impl ForegroundMask {
+    /// The tight bounding box as a continuous pixel box, covering the full area of the
+    /// outermost foreground pixels.
+    pub fn pixel_bounding_box(&self) -> Option<PixelBox> {
+        self.bounding_box.map(|(min, max)| PixelBox {
+            min: PixelPoint {
+                x: min.x as f64,
+                y: min.y as f64,
+            },
+            max: PixelPoint {
+                x: (max.x + 1) as f64,
+                y: (max.y + 1) as f64,
+            },
+        })
+    }
+
+    /// The mask covering the searched rectangles of both masks. A pixel is foreground
+    /// if it is foreground in either of them.
+    pub fn union(&self, other: &ForegroundMask) -> ForegroundMask {
+        let origin = Point {
+            x: self.origin.x.min(other.origin.x),
+            y: self.origin.y.min(other.origin.y),
+        };
+        let right = (self.origin.x + self.width).max(other.origin.x + other.width);
+        let bottom = (self.origin.y + self.height).max(other.origin.y + other.height);
+        let (width, height) = (right - origin.x, bottom - origin.y);
+
+        let mut mask = vec![false; (width * height) as usize];
+        for source in [self, other] {
+            for (i, _) in source.mask.iter().enumerate().filter(|(_, set)| **set) {
+                let x = source.origin.x + i as u32 % source.width - origin.x;
+                let y = source.origin.y + i as u32 / source.width - origin.y;
+                mask[(y * width + x) as usize] = true;
+            }
+        }
+
+        let pixel_count = mask.iter().filter(|set| **set).count();
+        let bounding_box = match (self.bounding_box, other.bounding_box) {
+            (Some((a_min, a_max)), Some((b_min, b_max))) => Some((
+                Point { x: a_min.x.min(b_min.x), y: a_min.y.min(b_min.y) },
+                Point { x: a_max.x.max(b_max.x), y: a_max.y.max(b_max.y) },
+            )),
+            (a, b) => a.or(b),
+        };
+        ForegroundMask {
+            origin,
+            width,
+            height,
+            mask,
+            pixel_count,
+            bounding_box,
+        }
+    }
+}
+
+impl SmartBlob {
+    /// Combines two fragments of one object into a single blob. The result keeps the
+    /// lower of the two IDs; centers and anomaly scores are weighted by chunk counts.
+    pub fn merge(&self, other: &SmartBlob) -> SmartBlob {
+        let (a, b) = (self.bounding_box, other.bounding_box);
+        let bounding_box = (
+            Point { x: a.0.x.min(b.0.x), y: a.0.y.min(b.0.y) },
+            Point { x: a.1.x.max(b.1.x), y: a.1.y.max(b.1.y) },
+        );
+        let mut chunk_coords = self.chunk_coords.clone();
+        chunk_coords.extend(other.chunk_coords.iter().filter(|p| !self.chunk_coords.contains(p)));
+
+        let size_in_chunks = chunk_coords.len();
+        let weight = |blob: &SmartBlob| blob.size_in_chunks as f64 / (self.size_in_chunks + other.size_in_chunks).max(1) as f64;
+        let (self_weight, other_weight) = (weight(self), weight(other));
+        let center_of_mass = (
+            self.center_of_mass.0 * self_weight + other.center_of_mass.0 * other_weight,
+            self.center_of_mass.1 * self_weight + other.center_of_mass.1 * other_weight,
+        );
+
+        let anomalous_chunk_count = self.anomalous_chunk_count + other.anomalous_chunk_count;
+        let scored = anomalous_chunk_count.max(1) as f64;
+        let average = |score: fn(&AnomalyDetails) -> f64| {
+            (score(&self.average_anomaly) * self.anomalous_chunk_count as f64
+                + score(&other.average_anomaly) * other.anomalous_chunk_count as f64)
+                / scored
+        };
+        let average_anomaly = AnomalyDetails {
+            luminance_score: average(|d| d.luminance_score),
+            color_score: average(|d| d.color_score),
+            hue_score: average(|d| d.hue_score),
+        };
+
+        let appearance = match (&self.appearance, &other.appearance) {
+            (Some(a), Some(b)) => Some(a.combine(b, other_weight as f32)),
+            (a, b) => a.clone().or_else(|| b.clone()),
+        };
+        let foreground = match (&self.foreground, &other.foreground) {
+            (Some(a), Some(b)) => Some(a.union(b)),
+            (a, b) => a.clone().or_else(|| b.clone()),
+        };
+
+        SmartBlob {
+            id: self.id.min(other.id),
+            shape: describe_shape(&chunk_coords, bounding_box),
+            bounding_box,
+            chunk_coords,
+            size_in_chunks,
+            normalized_size: self.normalized_size + other.normalized_size,
+            anomalous_chunk_count,
+            predictable_chunk_count: self.predictable_chunk_count + other.predictable_chunk_count,
+            average_anomaly,
+            center_of_mass,
+            appearance,
+            foreground,
+        }
+    }
+
+    /// The pixel area covered by the blob's chunk bounding box.
+    pub fn pixel_bounding_box(&self, geometry: &FrameGeometry) -> PixelBox {
+        geometry.grid_box_to_pixel(self.bounding_box)
+    }
+
+    /// The blob's chunk bounding box relative to the image size.
+    pub fn normalized_bounding_box(&self, geometry: &FrameGeometry) -> NormalizedBox {
+        geometry.grid_box_to_normalized(self.bounding_box)
+    }
+
+    /// The blob's center of mass in pixels.
+    pub fn pixel_center_of_mass(&self, geometry: &FrameGeometry) -> PixelPoint {
+        geometry.grid_to_pixel(self.center_of_mass)
+    }
+
+    /// The blob's center of mass relative to the image size.
+    pub fn normalized_center_of_mass(&self, geometry: &FrameGeometry) -> NormalizedPoint {
+        geometry.grid_to_normalized(self.center_of_mass)
+    }
 }
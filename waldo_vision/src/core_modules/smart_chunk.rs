@@ -19,9 +19,12 @@
 //     anomaly. While it uses luminance as the primary trigger for an event, it enriches
 //     that event with statistical scores from all other tracked dimensions, allowing
 //     higher-level modules to make more intelligent decisions.
+// 5.  **Depth Awareness**: It accepts chunks of any channel depth. Its history keeps
+//     the full-precision average of each frame, and the fixed noise floor (defined on
+//     the 8-bit scale) is rescaled to the `BitDepth` of the incoming data.
 
 use crate::core_modules::chunk::chunk::Chunk;
-use crate::core_modules::pixel::pixel::Pixel;
+use crate::core_modules::pixel::pixel::{BitDepth, LuminanceModel, Pixel16, PixelData};
 use crate::core_modules::smart_pixel::smart_pixel::{
     HueDifference, LuminanceDelta, SmartPixel,
 };
@@ -29,6 +32,7 @@ use std::collections::VecDeque;
 
 const HISTORY_WINDOW_SIZE: usize = 20;
 const ANOMALY_THRESHOLD_STD_DEV: f64 = 3.0;
+/// The luminance noise floor on the 8-bit scale. Rescaled for higher bit depths.
 const STABLE_LUMINANCE_THRESHOLD: f64 = 2.0;
 
 /// Holds the multi-dimensional signature of a detected anomaly.
@@ -63,10 +67,15 @@ pub struct SmartChunk {
     pub chunk_x: u32,
     /// The row index of this chunk in the main grid.
     pub chunk_y: u32,
+    /// The luminance standard and transfer model used to summarize incoming chunks.
+    pub luminance_model: LuminanceModel,
+    /// The bit depth of the data this chunk is currently learning from.
+    pub bit_depth: BitDepth,
 
     // --- Temporal History ---
-    /// A sliding window of the average `Pixel` value for this chunk's location over the last N frames.
-    average_pixel_history: VecDeque<Pixel>,
+    /// A sliding window of the average pixel value for this chunk's location over the last N frames.
+    /// Stored in the 16-bit container so that high bit-depth averages keep their precision.
+    average_pixel_history: VecDeque<Pixel16>,
     /// A sliding window of the calculated luminance difference between frames.
     luminance_delta_history: VecDeque<LuminanceDelta>,
     /// A sliding window of the calculated color difference between frames.
@@ -95,9 +104,16 @@ pub struct SmartChunk {
 
 impl SmartChunk {
     pub fn new(chunk_x: u32, chunk_y: u32) -> Self {
+        Self::with_luminance_model(chunk_x, chunk_y, LuminanceModel::default())
+    }
+
+    /// Creates a `SmartChunk` that measures luminance with the given model.
+    pub fn with_luminance_model(chunk_x: u32, chunk_y: u32, luminance_model: LuminanceModel) -> Self {
         Self {
             chunk_x,
             chunk_y,
+            luminance_model,
+            bit_depth: BitDepth::Eight,
             average_pixel_history: VecDeque::with_capacity(HISTORY_WINDOW_SIZE + 1),
             luminance_delta_history: VecDeque::with_capacity(HISTORY_WINDOW_SIZE),
             color_delta_history: VecDeque::with_capacity(HISTORY_WINDOW_SIZE),
@@ -113,11 +129,29 @@ impl SmartChunk {
     }
 
     pub fn update(&mut self, new_chunk: &Chunk) {
-        let new_average_pixel = new_chunk.average_pixel();
+        self.update_at_depth(new_chunk, BitDepth::Eight);
+    }
+
+    /// Updates the chunk with data of any channel depth. Deltas are computed on the
+    /// depth's own value range; a change of depth restarts the learning phase.
+    pub fn update_at_depth<P: PixelData>(&mut self, new_chunk: &Chunk<P>, depth: BitDepth) {
+        let new_average_pixel = new_chunk.average_pixel_at_depth(&self.luminance_model, depth);
+        self.update_with_average(&new_average_pixel, depth);
+    }
+
+    /// Updates the chunk from an already summarized average pixel. This is the entry
+    /// point for sources, such as Bayer mosaics, whose averages are computed without
+    /// first materializing a `Chunk`.
+    pub fn update_with_average<P: PixelData>(&mut self, average_pixel: &P, depth: BitDepth) {
+        if depth != self.bit_depth {
+            self.reset_history(depth);
+        }
+
+        let new_average_pixel = Pixel16::from_channels(average_pixel.channels());
 
         if let Some(previous_pixel) = self.average_pixel_history.back() {
-            let smart_new = SmartPixel::new(new_average_pixel.clone());
-            let smart_prev = SmartPixel::new(previous_pixel.clone());
+            let smart_new = SmartPixel::at_depth(new_average_pixel.clone(), &self.luminance_model, depth);
+            let smart_prev = SmartPixel::at_depth(previous_pixel.clone(), &self.luminance_model, depth);
 
             let new_lum_delta = smart_new.delta_luminance(&smart_prev);
             let new_col_delta = smart_new.delta_color(&smart_prev);
@@ -136,6 +170,15 @@ impl SmartChunk {
         Self::update_history_generic(&mut self.average_pixel_history, new_average_pixel);
     }
 
+    fn reset_history(&mut self, depth: BitDepth) {
+        self.bit_depth = depth;
+        self.average_pixel_history.clear();
+        self.luminance_delta_history.clear();
+        self.color_delta_history.clear();
+        self.hue_difference_history.clear();
+        self.status = ChunkStatus::Learning;
+    }
+
     fn update_history_generic<T>(history: &mut VecDeque<T>, new_value: T) {
         history.push_back(new_value);
         if history.len() > HISTORY_WINDOW_SIZE {
@@ -165,7 +208,7 @@ impl SmartChunk {
 
     /// Analyzes the latest deltas to set the chunk's status.
     fn analyze_status(&mut self, new_lum_delta: f64, new_col_delta: f64, new_hue_diff: f64) {
-        if new_lum_delta < STABLE_LUMINANCE_THRESHOLD {
+        if new_lum_delta < STABLE_LUMINANCE_THRESHOLD * self.bit_depth.threshold_scale() {
             self.status = ChunkStatus::Stable;
             return;
         }
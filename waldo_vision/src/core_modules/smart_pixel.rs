@@ -20,40 +20,54 @@
 pub mod smart_pixel {
     use crate::core_modules::pixel::pixel::*;
 
-    pub type ColorDelta = u16;
+    pub type ColorDelta = u32;
     pub type LuminanceDelta = f64;
     pub type HueDifference = f64;
 
-    /// An analytical tool that wraps a `Pixel` to provide optimized comparison methods.
-    pub struct SmartPixel {
-        /// The raw `Pixel` data this `SmartPixel` is analyzing.
-        pub pixel: Pixel,
+    /// An analytical tool that wraps a pixel to provide optimized comparison methods.
+    /// Works on any `PixelData`, so deltas of 16-bit sources keep their full precision.
+    pub struct SmartPixel<P: PixelData = Pixel> {
+        /// The raw pixel data this `SmartPixel` is analyzing.
+        pub pixel: P,
         /// The pre-calculated sum of the RGB channels, cached for performance.
         sum: Sum,
         /// The pre-calculated luminance of the pixel, cached for performance.
         luminance: Luminance,
     }
 
-    impl SmartPixel {
+    impl SmartPixel<Pixel> {
         pub fn new(pixel: Pixel) -> Self {
+            Self::with_model(pixel, &LuminanceModel::default())
+        }
+
+        /// Creates a `SmartPixel` whose cached luminance follows the given model.
+        pub fn with_model(pixel: Pixel, model: &LuminanceModel) -> Self {
+            Self::at_depth(pixel, model, BitDepth::Eight)
+        }
+    }
+
+    impl<P: PixelData> SmartPixel<P> {
+        /// Creates a `SmartPixel` for data of the given bit depth. Luminance is cached
+        /// on that depth's value range.
+        pub fn at_depth(pixel: P, model: &LuminanceModel, depth: BitDepth) -> Self {
             Self {
-                sum: pixel.sum(),
-                luminance: pixel.luminance(),
+                sum: pixel.channel_sum(),
+                luminance: model.luminance_at(&pixel, depth),
                 pixel,
             }
         }
 
-        pub fn delta_color(&self, other: &SmartPixel) -> ColorDelta {
+        pub fn delta_color(&self, other: &SmartPixel<P>) -> ColorDelta {
             (self.sum - other.sum).abs() as ColorDelta
         }
 
-        pub fn delta_luminance(&self, other: &SmartPixel) -> LuminanceDelta {
+        pub fn delta_luminance(&self, other: &SmartPixel<P>) -> LuminanceDelta {
             (self.luminance - other.luminance).abs()
         }
 
-        pub fn hue_difference(&self, other: &SmartPixel) -> HueDifference {
-            let (r1, g1, b1) = self.pixel.color_ratios();
-            let (r2, g2, b2) = other.pixel.color_ratios();
+        pub fn hue_difference(&self, other: &SmartPixel<P>) -> HueDifference {
+            let (r1, g1, b1) = self.pixel.channel_ratios();
+            let (r2, g2, b2) = other.pixel.channel_ratios();
 
             let diff = (r1 - r2).abs() + (g1 - g2).abs() + (b1 - b2).abs();
 
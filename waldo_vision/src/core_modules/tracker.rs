@@ -9,10 +9,10 @@
 //
 // Key architectural principles:
 // 1.  **Hierarchical Clustering**: Before tracking, it performs a "merge and absorb"
-//     pass on the raw blobs. It uses spatial proximity and signature similarity
-//     (`hue_difference`) to merge fragmented blobs into single, coherent objects.
-//     This solves the problem of a single real-world object being detected as
-//     multiple, separate blobs.
+//     pass on the raw blobs. Blobs that lie within an existing track's predicted
+//     extent, move with that track, and are close to each other are merged into a
+//     single, coherent object. This solves the problem of a single real-world object
+//     being detected as multiple, separate blobs (a head and a torso).
 // 2.  **Stateful Tracking**: It uses a state machine (`TrackedState`) for each
 //     `TrackedBlob`. This allows the system to distinguish between a `New` object,
 //     a predictably `Tracking` object, and an `Anomalous` object that has changed
@@ -20,18 +20,396 @@
 // 3.  **Behavioral Anomaly Detection**: The transition to the `Anomalous` state is
 //     driven by statistical analysis of an object's *own history*. It detects
 //     unpredictable changes in physical properties (acceleration, size change) or
-//     color signature, while ignoring stable changes in lighting.
-// 4.  **Lifecycle Management**: It manages the birth, life, and death of a track,
-//     handling occlusion and re-acquisition gracefully.
+//     color signature, while ignoring stable changes in lighting. Each detector that
+//     fires leaves an `AnomalyReason` with its score and threshold, so consumers can
+//     tell "sudden acceleration" from "changed color".
+// 4.  **Global Assignment**: Tracks and detections can be paired greedily, track by
+//     track, or by solving a minimum-cost assignment over all pairs at once (the
+//     Hungarian method). The global mode prevents an older track from stealing a
+//     detection that fits a newer track better, which swaps IDs when objects cross.
+//     The pairing cost weighs several cues (distance from the prediction, bounding
+//     box overlap, size ratio, appearance), and the breakdown of every pair considered
+//     is kept as per-frame diagnostics.
+// 5.  **Motion Models**: A track predicts its next position either from its last
+//     observed velocity, or with a constant-velocity Kalman filter that smooths the
+//     noisy centroids and gates candidate detections by Mahalanobis distance.
+// 6.  **Lifecycle Management**: It manages the birth, life, and death of a track,
+//     handling occlusion and re-acquisition gracefully. An unseen track coasts along
+//     its predicted path with a widening gate for a configurable time,
+//     and a detection that fits it in that window recovers the track (and its ID)
+//     instead of starting a new one.
+// 7.  **Lineage**: When a new track breaks away from an existing one that shrinks by
+//     about as much (a group separating) or a track disappears into a neighbour that grows (a person getting
+//     into a car), the relationship is recorded on the tracks and reported as a
+//     `LineageEvent`.
+// 8.  **Event Stream**: Every change in a track's life (started, confirmed, lost,
+//     recovered, ended, split off, merged away) is emitted as a `TrackEvent` with the
+//     frame it happened in, so consumers do not have to diff track lists themselves.
 
-use crate::core_modules::smart_blob::SmartBlob;
+use crate::core_modules::assignment;
+use crate::core_modules::geometry::{Distance, FrameGeometry, NormalizedPoint, PixelPoint};
+use crate::core_modules::kalman::{KalmanConfig, KalmanFilter};
+use crate::core_modules::smart_blob::{Point, SmartBlob};
 use crate::core_modules::smart_chunk::AnomalyDetails;
 use crate::pipeline::PipelineConfig;
 use std::collections::{HashMap, HashSet, VecDeque};
+use std::fmt;
 
-const HISTORY_SIZE: usize = 15;
-const MAX_FRAMES_SINCE_SEEN: u32 = 5;
-const DISTANCE_THRESHOLD: f64 = 5.0;
+/// How far, in chunks, a fragment may reach outside its track's predicted extent.
+const FRAGMENT_EXTENT_MARGIN: f64 = 1.0;
+/// The largest gap, in chunks, between two fragments of the same object.
+const FRAGMENT_GAP: u32 = 2;
+/// The share of a new detection's size that a track must have lost since the last frame
+/// for the detection to count as split off from it.
+const SPLIT_SIZE_SHARE: f64 = 0.5;
+
+/// Settings of the tracker. Distances are independent of the chunk size and durations
+/// are in seconds; both are converted with the pipeline geometry and `frame_rate`.
+///
+/// At 30 fps with 10x10 pixel chunks, the defaults match detections exactly as the
+/// tracker's former fixed settings did (a 5-chunk gate, 15 frames of history, `New`
+/// for 5 frames). Two defaults differ from the former behavior:
+/// - `coast_duration` is 1 s (30 frames); unseen tracks used to end after 5 frames.
+/// - `gate_growth` widens the gate of unseen tracks; the former gate never widened.
+#[derive(Debug, Clone, PartialEq)]
+pub struct TrackerConfig {
+    /// How tracks are paired with new detections. Default: `Greedy`.
+    pub assignment: TrackAssignment,
+    /// How tracks predict motion and gate candidate detections. Default: `LastVelocity`.
+    /// The `Kalman` noise settings are per frame and are not scaled by `frame_rate`.
+    pub motion_model: MotionModel,
+    /// The weights of the cues combined into the association cost. Default: see
+    /// `AssociationWeights`.
+    pub association_weights: AssociationWeights,
+    /// The frame rate of the input, in frames per second. Default: 30.
+    pub frame_rate: f64,
+    /// How much of each track's recent history is kept for behavioral analysis.
+    /// Default: 0.5 s (15 frames at 30 fps).
+    pub history_duration: f64,
+    /// How long a track is reported as `New` before its behavior is analyzed. Replaces the
+    /// former `PipelineConfig::new_age_threshold` (in frames: `frames / frame_rate`).
+    /// Default: 5 frames at 30 fps.
+    pub new_track_duration: f64,
+    /// How long a track may go unseen, coasting along its predicted path, and still be
+    /// re-acquired with its ID. Tracks unseen for longer end. Default: 1 s (30 frames at
+    /// 30 fps), long enough to bridge a typical occlusion.
+    pub coast_duration: f64,
+    /// The distance from its predicted position at which a detection no longer fits a
+    /// track, for the `LastVelocity` model. Default: 50 pixels (5 chunks of 10 pixels).
+    pub gate_distance: Distance,
+    /// How much the `LastVelocity` gate widens for each second a track goes unseen.
+    /// The `Kalman` model widens its gate through its growing covariance instead.
+    /// Default: 150 pixels per second (half a 10-pixel chunk per frame at 30 fps).
+    pub gate_growth: Distance,
+    /// The z-score above which a change in a track's motion, size or hue marks it
+    /// `Anomalous`. Replaces the former `PipelineConfig::behavioral_anomaly_threshold`.
+    /// Default: 3.0.
+    pub behavioral_anomaly_threshold: f64,
+}
+
+impl Default for TrackerConfig {
+    fn default() -> Self {
+        Self {
+            assignment: TrackAssignment::default(),
+            motion_model: MotionModel::default(),
+            association_weights: AssociationWeights::default(),
+            frame_rate: 30.0,
+            history_duration: 0.5,
+            new_track_duration: 5.0 / 30.0,
+            coast_duration: 1.0,
+            gate_distance: Distance::Pixels(50.0),
+            gate_growth: Distance::Pixels(150.0),
+            behavioral_anomaly_threshold: 3.0,
+        }
+    }
+}
+
+/// A `TrackerConfig` value that cannot be used.
+#[derive(Debug, Clone, PartialEq)]
+pub enum TrackerConfigError {
+    /// The frame rate is not a positive, finite number.
+    InvalidFrameRate(f64),
+    /// A duration is negative or not finite.
+    InvalidDuration { field: &'static str, seconds: f64 },
+    /// A distance is negative or not finite, or the gate distance is zero.
+    InvalidDistance { field: &'static str, distance: Distance },
+    /// An association weight is negative or not finite.
+    InvalidWeight { field: &'static str, weight: f64 },
+    /// A Kalman noise or gate setting is not a positive, finite number.
+    InvalidKalmanConfig(KalmanConfig),
+    /// The history is shorter than the two frames needed to measure a velocity.
+    HistoryTooShort { frames: usize },
+    /// The behavioral anomaly threshold is not a positive, finite number.
+    InvalidAnomalyThreshold(f64),
+}
+
+impl fmt::Display for TrackerConfigError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            TrackerConfigError::InvalidFrameRate(frame_rate) => {
+                write!(f, "frame rate must be positive, got {}", frame_rate)
+            }
+            TrackerConfigError::InvalidDuration { field, seconds } => {
+                write!(f, "{} must be a non-negative number of seconds, got {}", field, seconds)
+            }
+            TrackerConfigError::InvalidDistance { field, distance } => {
+                write!(f, "{} must be a positive distance, got {:?}", field, distance)
+            }
+            TrackerConfigError::InvalidWeight { field, weight } => {
+                write!(f, "association weight {} must be non-negative, got {}", field, weight)
+            }
+            TrackerConfigError::InvalidKalmanConfig(config) => {
+                write!(f, "Kalman noise and gate settings must be positive, got {:?}", config)
+            }
+            TrackerConfigError::HistoryTooShort { frames } => {
+                write!(f, "history must cover at least 2 frames, got {}", frames)
+            }
+            TrackerConfigError::InvalidAnomalyThreshold(threshold) => {
+                write!(f, "behavioral anomaly threshold must be positive, got {}", threshold)
+            }
+        }
+    }
+}
+
+impl std::error::Error for TrackerConfigError {}
+
+impl TrackerConfig {
+    /// Checks that every setting is usable.
+    pub fn validate(&self) -> Result<(), TrackerConfigError> {
+        if !(self.frame_rate.is_finite() && self.frame_rate > 0.0) {
+            return Err(TrackerConfigError::InvalidFrameRate(self.frame_rate));
+        }
+        let durations = [
+            ("history_duration", self.history_duration),
+            ("new_track_duration", self.new_track_duration),
+            ("coast_duration", self.coast_duration),
+        ];
+        for (field, seconds) in durations {
+            if !(seconds.is_finite() && seconds >= 0.0) {
+                return Err(TrackerConfigError::InvalidDuration { field, seconds });
+            }
+        }
+        // The gate may stop growing, but not have zero size.
+        let distances = [("gate_distance", self.gate_distance, false), ("gate_growth", self.gate_growth, true)];
+        for (field, distance, zero_allowed) in distances {
+            let (Distance::Pixels(value) | Distance::Normalized(value)) = distance;
+            if !(value.is_finite() && (value > 0.0 || (zero_allowed && value == 0.0))) {
+                return Err(TrackerConfigError::InvalidDistance { field, distance });
+            }
+        }
+        let weights = self.association_weights;
+        let weights = [
+            ("distance", weights.distance),
+            ("overlap", weights.overlap),
+            ("size", weights.size),
+            ("appearance", weights.appearance),
+        ];
+        for (field, weight) in weights {
+            if !(weight.is_finite() && weight >= 0.0) {
+                return Err(TrackerConfigError::InvalidWeight { field, weight });
+            }
+        }
+        if let MotionModel::Kalman(config) = self.motion_model {
+            let settings = [config.process_noise, config.measurement_noise, config.initial_velocity_variance, config.gate];
+            if !settings.iter().all(|value| value.is_finite() && *value > 0.0) {
+                return Err(TrackerConfigError::InvalidKalmanConfig(config));
+            }
+        }
+        let frames = self.frames(self.history_duration) as usize;
+        if frames < 2 {
+            return Err(TrackerConfigError::HistoryTooShort { frames });
+        }
+        let threshold = self.behavioral_anomaly_threshold;
+        if !(threshold.is_finite() && threshold > 0.0) {
+            return Err(TrackerConfigError::InvalidAnomalyThreshold(threshold));
+        }
+        Ok(())
+    }
+
+    /// A duration in seconds as a whole number of frames.
+    fn frames(&self, seconds: f64) -> u32 {
+        (seconds * self.frame_rate).round() as u32
+    }
+
+    /// The settings in frames and grid units for the given geometry.
+    fn limits(&self, geometry: &FrameGeometry) -> TrackingLimits {
+        TrackingLimits {
+            history_size: (self.frames(self.history_duration) as usize).max(2),
+            new_track_frames: self.frames(self.new_track_duration),
+            coast_frames: self.frames(self.coast_duration),
+            gate_distance: geometry.distance_to_grid(self.gate_distance),
+            gate_growth: geometry.distance_to_grid(self.gate_growth) / self.frame_rate,
+        }
+    }
+}
+
+/// A `TrackerConfig` resolved to frames and grid units.
+#[derive(Debug, Clone, Copy)]
+struct TrackingLimits {
+    history_size: usize,
+    new_track_frames: u32,
+    coast_frames: u32,
+    gate_distance: f64,
+    /// Gate widening per unseen frame.
+    gate_growth: f64,
+}
+
+/// A change in the lineage of tracks, detected in one call to `Tracker::update`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum LineageEvent {
+    /// New tracks broke away from an existing track, which continues.
+    Split { parent_id: u64, child_ids: Vec<u64> },
+    /// Tracks disappeared into another track, which continues. The absorbed tracks end.
+    Merge { parent_ids: Vec<u64>, child_id: u64 },
+}
+
+/// A change in the life of a track.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum TrackEventKind {
+    /// The track was created for an unmatched detection.
+    Started,
+    /// The track outlived its `New` period and its behavior is now analyzed.
+    Confirmed,
+    /// The track went unseen and started coasting.
+    Lost,
+    /// The track was re-acquired after going unseen for `frames_unseen` frames.
+    Recovered { frames_unseen: u32 },
+    /// The track was removed, after coasting too long or merging into another track.
+    Ended,
+    /// The track started as a piece that broke away from track `parent_id`. Follows its
+    /// `Started` event.
+    Split { parent_id: u64 },
+    /// The track's object merged into track `into_id`. Followed by its `Ended` event.
+    Merged { into_id: u64 },
+}
+
+/// A lifecycle event of one track.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct TrackEvent {
+    pub track_id: u64,
+    /// The frame the event happened in, counting from 1 for the first call to `update`.
+    pub frame: u64,
+    pub kind: TrackEventKind,
+}
+
+/// A track that was re-acquired after going unseen.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct TrackRecovered {
+    pub track_id: u64,
+    /// How many frames the track went unseen.
+    pub frames_unseen: u32,
+}
+
+/// How tracks are paired with the detections of a new frame.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum TrackAssignment {
+    /// Each track, oldest first, takes the lowest-cost detection still available.
+    #[default]
+    Greedy,
+    /// The pairing with the lowest total cost over all tracks, found with the
+    /// Hungarian method.
+    Hungarian,
+}
+
+/// The weights of the terms of the association cost. Each term is 0.0 for a perfect
+/// fit; the distance term is 1.0 at the edge of the gate, the others at their worst.
+/// The default weighs the distance alone, as the tracker always has; the other cues
+/// are opt-in.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct AssociationWeights {
+    /// Distance from the track's predicted position.
+    pub distance: f64,
+    /// One minus the IoU of the detection's bounding box and the track's box moved to
+    /// its predicted position.
+    pub overlap: f64,
+    /// One minus the ratio of the smaller to the larger normalized size.
+    pub size: f64,
+    /// The Bhattacharyya distance of the color histograms when both blobs have one,
+    /// otherwise the relative difference of the anomaly signatures.
+    pub appearance: f64,
+}
+
+impl Default for AssociationWeights {
+    fn default() -> Self {
+        Self {
+            distance: 1.0,
+            overlap: 0.0,
+            size: 0.0,
+            appearance: 0.0,
+        }
+    }
+}
+
+/// The breakdown of the cost of pairing a track with a detection. The unweighted terms
+/// are reported for every pair, even those outside the gate.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct AssociationCost {
+    /// The gate distance term, or `None` if the detection is outside the track's gate.
+    pub distance: Option<f64>,
+    pub overlap: f64,
+    pub size: f64,
+    pub appearance: f64,
+    /// The weighted sum of the terms, or infinity for pairs outside the gate.
+    pub total: f64,
+}
+
+/// One track-detection pair considered during matching, for diagnostics.
+#[derive(Debug, Clone, PartialEq)]
+pub struct AssociationCandidate {
+    pub track_id: u64,
+    /// The index of the detection among the frame's (merged) blobs.
+    pub detection_index: usize,
+    pub cost: AssociationCost,
+    /// Whether the pair was chosen.
+    pub matched: bool,
+}
+
+/// How a track predicts where its object will be in the next frame.
+#[derive(Debug, Clone, Copy, PartialEq, Default)]
+pub enum MotionModel {
+    /// The last position plus the last frame's velocity, gated by a fixed distance.
+    #[default]
+    LastVelocity,
+    /// A constant-velocity Kalman filter, gated by Mahalanobis distance.
+    Kalman(KalmanConfig),
+}
+
+/// The behavioral anomaly detectors of the tracker.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum AnomalyKind {
+    /// The velocity departed from the track's recent velocities.
+    Acceleration,
+    /// The size grew faster than the track's recent size changes.
+    SizeChange,
+    /// The hue anomaly score rose above the track's recent hue scores.
+    HueChange,
+}
+
+impl AnomalyKind {
+    /// A short human-readable description, for alerts.
+    pub fn description(&self) -> &'static str {
+        match self {
+            AnomalyKind::Acceleration => "sudden acceleration",
+            AnomalyKind::SizeChange => "sudden size change",
+            AnomalyKind::HueChange => "sudden color change",
+        }
+    }
+}
+
+/// Why a track was judged `Anomalous`: the detector that fired, its z-score against the
+/// track's own history, and the threshold it exceeded.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct AnomalyReason {
+    pub kind: AnomalyKind,
+    pub score: f64,
+    pub threshold: f64,
+}
+
+impl fmt::Display for AnomalyReason {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{} (score {:.2}, threshold {:.2})", self.kind.description(), self.score, self.threshold)
+    }
+}
 
 /// Represents the current behavioral state of a tracked object.
 #[derive(Debug, Clone, PartialEq)]
@@ -48,63 +426,129 @@ pub struct TrackedBlob {
     pub id: u64,
     pub state: TrackedState,
     pub latest_blob: SmartBlob,
+    /// Recent centers of mass, in grid units; see `pixel_position_history` and
+    /// `normalized_position_history`.
     pub position_history: VecDeque<(f64, f64)>,
-    pub size_history: VecDeque<usize>,
+    /// Recent normalized sizes (see `SmartBlob::normalized_size`).
+    pub size_history: VecDeque<f64>,
     pub velocity_history: VecDeque<(f64, f64)>,
     pub signature_history: VecDeque<AnomalyDetails>,
     pub velocity: (f64, f64),
     pub age: u32,
     pub frames_since_seen: u32,
+    /// The track this one split off from, if it started as part of another object.
     pub parent_id: Option<u64>,
+    /// The tracks that merged into this one.
+    pub merged_from: Vec<u64>,
+    /// The detectors that fired in the latest analysis, strongest first. Empty unless
+    /// the state is `Anomalous`.
+    pub anomaly_reasons: Vec<AnomalyReason>,
+    /// The Kalman filter of the track, holding its smoothed state and uncertainty.
+    /// `None` unless the `Kalman` motion model is configured.
+    pub motion: Option<KalmanFilter>,
 }
 
 impl TrackedBlob {
-    fn new(id: u64, blob: SmartBlob) -> Self {
-        let mut position_history = VecDeque::with_capacity(HISTORY_SIZE);
+    fn new(id: u64, blob: SmartBlob, motion_model: MotionModel, history_size: usize) -> Self {
+        let motion = match motion_model {
+            MotionModel::LastVelocity => None,
+            MotionModel::Kalman(config) => Some(KalmanFilter::new(blob.center_of_mass, config)),
+        };
+        let mut position_history = VecDeque::with_capacity(history_size);
         position_history.push_back(blob.center_of_mass);
-        let mut size_history = VecDeque::with_capacity(HISTORY_SIZE);
-        size_history.push_back(blob.size_in_chunks);
+        let mut size_history = VecDeque::with_capacity(history_size);
+        size_history.push_back(blob.normalized_size);
         Self {
             id,
             state: TrackedState::New,
             latest_blob: blob,
             position_history,
             size_history,
-            velocity_history: VecDeque::with_capacity(HISTORY_SIZE),
-            signature_history: VecDeque::with_capacity(HISTORY_SIZE),
+            velocity_history: VecDeque::with_capacity(history_size),
+            signature_history: VecDeque::with_capacity(history_size),
             velocity: (0.0, 0.0),
             age: 1,
             frames_since_seen: 0,
             parent_id: None,
+            merged_from: Vec::new(),
+            anomaly_reasons: Vec::new(),
+            motion,
         }
     }
 
-    fn update(&mut self, blob: SmartBlob) {
+    /// The smoothed position of the object, or its last measured position when no
+    /// Kalman filter is in use.
+    pub fn smoothed_position(&self) -> (f64, f64) {
+        self.motion.as_ref().map_or(self.latest_blob.center_of_mass, |filter| filter.position())
+    }
+
+    /// The smoothed velocity of the object in chunks per frame, or its last measured
+    /// velocity when no Kalman filter is in use.
+    pub fn smoothed_velocity(&self) -> (f64, f64) {
+        self.motion.as_ref().map_or(self.velocity, |filter| filter.velocity())
+    }
+
+    /// The recent positions of the object in pixels.
+    pub fn pixel_position_history(&self, geometry: &FrameGeometry) -> Vec<PixelPoint> {
+        self.position_history.iter().map(|p| geometry.grid_to_pixel(*p)).collect()
+    }
+
+    /// The recent positions of the object relative to the image size.
+    pub fn normalized_position_history(&self, geometry: &FrameGeometry) -> Vec<NormalizedPoint> {
+        self.position_history.iter().map(|p| geometry.grid_to_normalized(*p)).collect()
+    }
+
+    fn update(&mut self, blob: SmartBlob, history_size: usize) {
+        // Velocity is per frame, so a gap spanning several frames is divided by them.
+        let elapsed_frames = (self.frames_since_seen + 1) as f64;
         self.latest_blob = blob;
         self.age += 1;
         self.frames_since_seen = 0;
 
-        update_history(&mut self.position_history, self.latest_blob.center_of_mass);
-        update_history(&mut self.size_history, self.latest_blob.size_in_chunks);
-        update_history(&mut self.signature_history, self.latest_blob.average_anomaly.clone());
+        if let Some(filter) = &mut self.motion {
+            filter.update(self.latest_blob.center_of_mass);
+        }
+        update_history(&mut self.position_history, self.latest_blob.center_of_mass, history_size);
+        update_history(&mut self.size_history, self.latest_blob.normalized_size, history_size);
+        update_history(&mut self.signature_history, self.latest_blob.average_anomaly.clone(), history_size);
 
         if self.position_history.len() > 1 {
             let new_pos = self.position_history.back().unwrap();
             let old_pos = self.position_history.get(self.position_history.len() - 2).unwrap();
-            self.velocity = (new_pos.0 - old_pos.0, new_pos.1 - old_pos.1);
-            update_history(&mut self.velocity_history, self.velocity);
+            self.velocity = ((new_pos.0 - old_pos.0) / elapsed_frames, (new_pos.1 - old_pos.1) / elapsed_frames);
+            update_history(&mut self.velocity_history, self.velocity, history_size);
         }
     }
     
+    /// Where the object is expected in the current frame. A Kalman filter has already
+    /// been advanced to the current frame by `Tracker::update`; otherwise the last
+    /// velocity is extrapolated over every frame since the object was last seen.
     fn predict_next_position(&self) -> (f64, f64) {
+        if let Some(filter) = &self.motion {
+            return filter.position();
+        }
         let current_pos = self.latest_blob.center_of_mass;
-        (current_pos.0 + self.velocity.0, current_pos.1 + self.velocity.1)
+        let frames = (self.frames_since_seen + 1) as f64;
+        (current_pos.0 + self.velocity.0 * frames, current_pos.1 + self.velocity.1 * frames)
+    }
+
+    /// How far a detection is from this track's prediction, scaled so that the edge of
+    /// the gate is 1.0. `None` if the detection is outside the gate.
+    fn gate_distance(&self, blob: &SmartBlob, limits: &TrackingLimits) -> Option<f64> {
+        if let Some(filter) = &self.motion {
+            return filter.gate_distance(blob.center_of_mass);
+        }
+        let predicted_pos = self.predict_next_position();
+        let dist_sq = (predicted_pos.0 - blob.center_of_mass.0).powi(2) + (predicted_pos.1 - blob.center_of_mass.1).powi(2);
+        let dist = dist_sq.sqrt();
+        let threshold = limits.gate_distance + limits.gate_growth * self.frames_since_seen as f64;
+        (dist < threshold).then_some(dist / threshold)
     }
 }
 
-fn update_history<T>(history: &mut VecDeque<T>, new_value: T) {
+fn update_history<T>(history: &mut VecDeque<T>, new_value: T, history_size: usize) {
     history.push_back(new_value);
-    if history.len() > HISTORY_SIZE {
+    while history.len() > history_size {
         history.pop_front();
     }
 }
@@ -112,6 +556,16 @@ fn update_history<T>(history: &mut VecDeque<T>, new_value: T) {
 pub struct Tracker {
     tracked_blobs: Vec<TrackedBlob>,
     next_id: u64,
+    /// The cost breakdown of every pair considered in the last call to `update`.
+    association_diagnostics: Vec<AssociationCandidate>,
+    /// The splits and merges detected in the last call to `update`.
+    lineage_events: Vec<LineageEvent>,
+    /// The tracks re-acquired in the last call to `update`.
+    recovered_tracks: Vec<TrackRecovered>,
+    /// The lifecycle events of the last call to `update`.
+    track_events: Vec<TrackEvent>,
+    /// The number of calls to `update` so far.
+    frame_count: u64,
 }
 
 impl Tracker {
@@ -119,133 +573,463 @@ impl Tracker {
         Self {
             tracked_blobs: Vec::new(),
             next_id: 0,
+            association_diagnostics: Vec::new(),
+            lineage_events: Vec::new(),
+            recovered_tracks: Vec::new(),
+            track_events: Vec::new(),
+            frame_count: 0,
         }
     }
 
     pub fn update(&mut self, new_blobs: Vec<SmartBlob>, config: &PipelineConfig) -> &Vec<TrackedBlob> {
-        let coherent_blobs = self.merge_fragmented_blobs(new_blobs);
-        let (matches, unmatched_blobs_map) = self.match_blobs(coherent_blobs);
+        let limits = config.tracker.limits(&config.geometry());
+        self.frame_count += 1;
+        let frame = self.frame_count;
+        let mut events = Vec::new();
+        let mut emit = |track_id, kind| events.push(TrackEvent { track_id, frame, kind });
+        // The first analysis happens on a track's second frame, so that is the earliest
+        // it can leave the `New` state.
+        let confirmation_age = limits.new_track_frames.max(2);
+
+        for tracked_blob in &mut self.tracked_blobs {
+            if let Some(filter) = &mut tracked_blob.motion {
+                filter.predict();
+            }
+        }
+
+        let coherent_blobs = self.merge_fragmented_blobs(new_blobs, &limits);
+        let (matches, unmatched_blobs_map) = self.match_blobs(coherent_blobs, &config.tracker, &limits);
         let mut unmatched_blobs = unmatched_blobs_map;
 
         let mut updated_tracked_blobs = Vec::new();
         let mut matched_tracked_indices = HashSet::new();
+        self.recovered_tracks.clear();
 
         for (tracked_idx, blob_idx) in matches {
             let mut tracked_blob = self.tracked_blobs[tracked_idx].clone();
             if let Some(blob_data) = unmatched_blobs.remove(&blob_idx) {
-                tracked_blob.update(blob_data);
-                self.analyze_blob_behavior(&mut tracked_blob, config);
+                if tracked_blob.frames_since_seen > 0 {
+                    self.recovered_tracks.push(TrackRecovered {
+                        track_id: tracked_blob.id,
+                        frames_unseen: tracked_blob.frames_since_seen,
+                    });
+                    emit(tracked_blob.id, TrackEventKind::Recovered { frames_unseen: tracked_blob.frames_since_seen });
+                }
+                tracked_blob.update(blob_data, limits.history_size);
+                self.analyze_blob_behavior(&mut tracked_blob, &config.tracker, &limits);
+                if tracked_blob.age == confirmation_age {
+                    emit(tracked_blob.id, TrackEventKind::Confirmed);
+                }
                 updated_tracked_blobs.push(tracked_blob);
                 matched_tracked_indices.insert(tracked_idx);
             }
         }
+        self.lineage_events = self.find_merges(&mut updated_tracked_blobs, &matched_tracked_indices, &limits);
+        // A track that merged into another ends there instead of coasting, so it does
+        // not compete with the survivor for the merged detection.
+        let absorbed_ids: HashSet<u64> = self
+            .lineage_events
+            .iter()
+            .flat_map(|event| match event {
+                LineageEvent::Merge { parent_ids, .. } => parent_ids.clone(),
+                LineageEvent::Split { .. } => Vec::new(),
+            })
+            .collect();
+        for event in &self.lineage_events {
+            if let LineageEvent::Merge { parent_ids, child_id } = event {
+                for parent_id in parent_ids {
+                    emit(*parent_id, TrackEventKind::Merged { into_id: *child_id });
+                    emit(*parent_id, TrackEventKind::Ended);
+                }
+            }
+        }
 
         for (i, tracked_blob) in self.tracked_blobs.iter().enumerate() {
-            if !matched_tracked_indices.contains(&i) {
+            if !matched_tracked_indices.contains(&i) && !absorbed_ids.contains(&tracked_blob.id) {
                 let mut lost_blob = tracked_blob.clone();
                 lost_blob.frames_since_seen += 1;
                 lost_blob.state = TrackedState::Lost;
-                if lost_blob.frames_since_seen <= MAX_FRAMES_SINCE_SEEN {
+                if lost_blob.frames_since_seen == 1 {
+                    emit(lost_blob.id, TrackEventKind::Lost);
+                }
+                if lost_blob.frames_since_seen <= limits.coast_frames {
                     updated_tracked_blobs.push(lost_blob);
+                } else {
+                    emit(lost_blob.id, TrackEventKind::Ended);
                 }
             }
         }
 
-        for new_blob in unmatched_blobs.into_values() {
-            let new_tracked_blob = TrackedBlob::new(self.next_id, new_blob);
+        let mut unmatched_blobs: Vec<(usize, SmartBlob)> = unmatched_blobs.into_iter().collect();
+        unmatched_blobs.sort_by_key(|(blob_idx, _)| *blob_idx);
+        let mut splits: Vec<(u64, Vec<u64>)> = Vec::new();
+        for (_, new_blob) in unmatched_blobs {
+            let mut new_tracked_blob =
+                TrackedBlob::new(self.next_id, new_blob, config.tracker.motion_model, limits.history_size);
+            new_tracked_blob.parent_id = self.find_split_parent(&new_tracked_blob.latest_blob, &updated_tracked_blobs);
+            emit(new_tracked_blob.id, TrackEventKind::Started);
+            if let Some(parent_id) = new_tracked_blob.parent_id {
+                emit(new_tracked_blob.id, TrackEventKind::Split { parent_id });
+                match splits.iter_mut().find(|(id, _)| *id == parent_id) {
+                    Some((_, child_ids)) => child_ids.push(new_tracked_blob.id),
+                    None => splits.push((parent_id, vec![new_tracked_blob.id])),
+                }
+            }
             updated_tracked_blobs.push(new_tracked_blob);
             self.next_id += 1;
         }
+        self.lineage_events
+            .extend(splits.into_iter().map(|(parent_id, child_ids)| LineageEvent::Split { parent_id, child_ids }));
 
+        self.track_events = events;
         self.tracked_blobs = updated_tracked_blobs;
         &self.tracked_blobs
     }
 
-    fn merge_fragmented_blobs(&self, blobs: Vec<SmartBlob>) -> Vec<SmartBlob> {
-        blobs // Placeholder for future enhancement
-    }
-
-    fn match_blobs(&self, blobs: Vec<SmartBlob>) -> (Vec<(usize, usize)>, HashMap<usize, SmartBlob>) {
-        let mut matches = Vec::new();
-        let unmatched_blobs: HashMap<usize, SmartBlob> = blobs.into_iter().enumerate().collect();
-        let mut used_blob_indices = HashSet::new();
+    /// Merges blobs that are fragments of one tracked object. A blob is claimed by the
+    /// track whose predicted extent it overlaps and whose predicted motion it fits
+    /// (within the track's gate), preferring the closest prediction. Blobs claimed by
+    /// the same track are merged when they are within `FRAGMENT_GAP` of each other.
+    fn merge_fragmented_blobs(&self, blobs: Vec<SmartBlob>, limits: &TrackingLimits) -> Vec<SmartBlob> {
+        let claims: Vec<Option<usize>> = blobs
+            .iter()
+            .map(|blob| {
+                self.tracked_blobs
+                    .iter()
+                    .enumerate()
+                    .filter(|(_, tracked_blob)| {
+                        tracked_blob.frames_since_seen == 0 && overlaps_predicted_extent(tracked_blob, blob)
+                    })
+                    .filter_map(|(i, tracked_blob)| tracked_blob.gate_distance(blob, limits).map(|dist| (i, dist)))
+                    .min_by(|a, b| a.1.total_cmp(&b.1))
+                    .map(|(i, _)| i)
+            })
+            .collect();
 
-        for (i, tracked_blob) in self.tracked_blobs.iter().enumerate() {
-            let predicted_pos = tracked_blob.predict_next_position();
-            let mut best_match: Option<(usize, f64)> = None;
-
-            for (j, new_blob) in &unmatched_blobs {
-                if used_blob_indices.contains(j) { continue; }
-                let dist_sq = (predicted_pos.0 - new_blob.center_of_mass.0).powi(2) + (predicted_pos.1 - new_blob.center_of_mass.1).powi(2);
-                let dist = dist_sq.sqrt();
-                if dist < DISTANCE_THRESHOLD {
-                    if best_match.is_none() || dist < best_match.as_ref().unwrap().1 {
-                        best_match = Some((*j, dist));
+        let mut remaining: Vec<Option<SmartBlob>> = blobs.into_iter().map(Some).collect();
+        let mut merged_blobs = Vec::new();
+        for i in 0..remaining.len() {
+            let Some(mut blob) = remaining[i].take() else { continue };
+            if claims[i].is_some() {
+                // Absorb fragments until none of the same track is close enough to the
+                // growing blob.
+                while let Some(j) = (i + 1..remaining.len()).find(|&j| {
+                    claims[j] == claims[i]
+                        && remaining[j].as_ref().is_some_and(|other| box_gap(blob.bounding_box, other.bounding_box) <= FRAGMENT_GAP)
+                }) {
+                    if let Some(fragment) = remaining[j].take() {
+                        blob = blob.merge(&fragment);
                     }
                 }
             }
+            merged_blobs.push(blob);
+        }
+        merged_blobs
+    }
+
+    fn match_blobs(
+        &mut self,
+        blobs: Vec<SmartBlob>,
+        config: &TrackerConfig,
+        limits: &TrackingLimits,
+    ) -> (Vec<(usize, usize)>, HashMap<usize, SmartBlob>) {
+        let costs: Vec<Vec<AssociationCost>> = self
+            .tracked_blobs
+            .iter()
+            .map(|tracked_blob| {
+                blobs
+                    .iter()
+                    .map(|blob| association_cost(tracked_blob, blob, &config.association_weights, limits))
+                    .collect()
+            })
+            .collect();
+        let totals: Vec<Vec<f64>> = costs.iter().map(|row| row.iter().map(|cost| cost.total).collect()).collect();
+        let assignment = match config.assignment {
+            TrackAssignment::Greedy => greedy_assignment(&totals),
+            TrackAssignment::Hungarian => assignment::solve(&totals),
+        };
+
+        self.association_diagnostics = costs
+            .into_iter()
+            .enumerate()
+            .flat_map(|(i, row)| {
+                let track_id = self.tracked_blobs[i].id;
+                let assigned = assignment[i];
+                row.into_iter().enumerate().map(move |(j, cost)| AssociationCandidate {
+                    track_id,
+                    detection_index: j,
+                    cost,
+                    matched: assigned == Some(j),
+                })
+            })
+            .collect();
 
-            if let Some((j, _)) = best_match {
-                matches.push((i, j));
-                used_blob_indices.insert(j);
+        let matches = assignment
+            .into_iter()
+            .enumerate()
+            .filter_map(|(i, j)| j.map(|j| (i, j)))
+            .collect();
+        (matches, blobs.into_iter().enumerate().collect())
+    }
+
+    /// Finds tracks seen last frame that went unmatched because their object merged
+    /// into another track's detection: one that fits their predicted extent and motion
+    /// and is larger than the other track's previous detection. `updated` holds the
+    /// tracks matched this frame; the survivors record whom they absorbed.
+    fn find_merges(
+        &self,
+        updated: &mut [TrackedBlob],
+        matched_tracked_indices: &HashSet<usize>,
+        limits: &TrackingLimits,
+    ) -> Vec<LineageEvent> {
+        let mut merges: Vec<(u64, Vec<u64>)> = Vec::new();
+        for (i, absorbed) in self.tracked_blobs.iter().enumerate() {
+            if matched_tracked_indices.contains(&i) || absorbed.frames_since_seen != 0 {
+                continue;
+            }
+            let survivor = updated
+                .iter_mut()
+                .filter(|survivor| {
+                    let previous_size = self
+                        .tracked_blobs
+                        .iter()
+                        .find(|tracked_blob| tracked_blob.id == survivor.id)
+                        .map_or(f64::INFINITY, |tracked_blob| tracked_blob.latest_blob.normalized_size);
+                    survivor.latest_blob.normalized_size > previous_size
+                        && overlaps_predicted_extent(absorbed, &survivor.latest_blob)
+                })
+                .filter_map(|survivor| absorbed.gate_distance(&survivor.latest_blob, limits).map(|dist| (survivor, dist)))
+                .min_by(|a, b| a.1.total_cmp(&b.1))
+                .map(|(survivor, _)| survivor);
+
+            if let Some(survivor) = survivor {
+                survivor.merged_from.push(absorbed.id);
+                match merges.iter_mut().find(|(id, _)| *id == survivor.id) {
+                    Some((_, parent_ids)) => parent_ids.push(absorbed.id),
+                    None => merges.push((survivor.id, vec![absorbed.id])),
+                }
             }
         }
-        (matches, unmatched_blobs)
+        merges
+            .into_iter()
+            .map(|(child_id, parent_ids)| LineageEvent::Merge { parent_ids, child_id })
+            .collect()
+    }
+
+    /// The track a new detection split off from: one that was seen last frame, is
+    /// still matched this frame, whose predicted extent the detection overlaps, and
+    /// whose own detection shrank by a good share of the new detection's size. `updated`
+    /// holds the tracks matched this frame. The track with the nearest prediction wins.
+    fn find_split_parent(&self, blob: &SmartBlob, updated: &[TrackedBlob]) -> Option<u64> {
+        self.tracked_blobs
+            .iter()
+            .filter(|tracked_blob| {
+                let lost_size = updated
+                    .iter()
+                    .find(|current| current.id == tracked_blob.id && current.frames_since_seen == 0)
+                    .map(|current| tracked_blob.latest_blob.normalized_size - current.latest_blob.normalized_size);
+                tracked_blob.frames_since_seen == 0
+                    && overlaps_predicted_extent(tracked_blob, blob)
+                    && lost_size.is_some_and(|lost| lost >= SPLIT_SIZE_SHARE * blob.normalized_size)
+            })
+            .map(|tracked_blob| {
+                let predicted_pos = tracked_blob.predict_next_position();
+                let dist_sq = (predicted_pos.0 - blob.center_of_mass.0).powi(2) + (predicted_pos.1 - blob.center_of_mass.1).powi(2);
+                (tracked_blob.id, dist_sq)
+            })
+            .min_by(|a, b| a.1.total_cmp(&b.1))
+            .map(|(id, _)| id)
     }
 
-    fn analyze_blob_behavior(&self, blob: &mut TrackedBlob, config: &PipelineConfig) {
-        if blob.age < config.new_age_threshold {
+    fn analyze_blob_behavior(&self, blob: &mut TrackedBlob, config: &TrackerConfig, limits: &TrackingLimits) {
+        blob.anomaly_reasons.clear();
+        if blob.age < limits.new_track_frames {
             blob.state = TrackedState::New;
             return;
         }
 
-        let accel_anomaly = is_acceleration_anomalous(blob, config);
-        let size_anomaly = is_size_change_anomalous(blob, config);
-        let hue_anomaly = is_hue_change_anomalous(blob, config);
+        let min_history = limits.history_size / 2;
+        let threshold = config.behavioral_anomaly_threshold;
+        let scores = [
+            (AnomalyKind::Acceleration, acceleration_z_score(blob, min_history)),
+            (AnomalyKind::SizeChange, size_change_z_score(blob, min_history)),
+            (AnomalyKind::HueChange, hue_change_z_score(blob, min_history)),
+        ];
+        blob.anomaly_reasons = scores
+            .into_iter()
+            .filter_map(|(kind, score)| score.filter(|score| *score > threshold).map(|score| AnomalyReason { kind, score, threshold }))
+            .collect();
+        blob.anomaly_reasons.sort_by(|a, b| b.score.total_cmp(&a.score));
 
-        if accel_anomaly || size_anomaly || hue_anomaly {
-            blob.state = TrackedState::Anomalous;
-        } else {
+        if blob.anomaly_reasons.is_empty() {
             blob.state = TrackedState::Tracking;
+        } else {
+            blob.state = TrackedState::Anomalous;
         }
     }
 
     pub fn get_tracked_blobs(&self) -> &Vec<TrackedBlob> {
         &self.tracked_blobs
     }
+
+    pub fn get_association_diagnostics(&self) -> &Vec<AssociationCandidate> {
+        &self.association_diagnostics
+    }
+
+    pub fn get_lineage_events(&self) -> &Vec<LineageEvent> {
+        &self.lineage_events
+    }
+
+    pub fn get_recovered_tracks(&self) -> &Vec<TrackRecovered> {
+        &self.recovered_tracks
+    }
+
+    /// The lifecycle events of the last call to `update`, in the order they happened:
+    /// recoveries and confirmations of matched tracks, merges, tracks lost or ended,
+    /// and finally the tracks started (each followed by its `Split`, if any).
+    pub fn get_track_events(&self) -> &Vec<TrackEvent> {
+        &self.track_events
+    }
+}
+
+/// The cost of pairing a track with a detection, broken down by cue.
+fn association_cost(
+    tracked_blob: &TrackedBlob,
+    blob: &SmartBlob,
+    weights: &AssociationWeights,
+    limits: &TrackingLimits,
+) -> AssociationCost {
+    let distance = tracked_blob.gate_distance(blob, limits);
+    let last_blob = &tracked_blob.latest_blob;
+
+    let predicted_pos = tracked_blob.predict_next_position();
+    let shift = (predicted_pos.0 - last_blob.center_of_mass.0, predicted_pos.1 - last_blob.center_of_mass.1);
+    let overlap = 1.0 - bounding_box_iou(last_blob.bounding_box, shift, blob.bounding_box);
+
+    let (old_size, new_size) = (last_blob.normalized_size, blob.normalized_size);
+    let size = 1.0 - old_size.min(new_size) / old_size.max(new_size).max(f64::EPSILON);
+
+    let appearance = match (&last_blob.appearance, &blob.appearance) {
+        (Some(old), Some(new)) => old.bhattacharyya_distance(new),
+        _ => signature_difference(&last_blob.average_anomaly, &blob.average_anomaly),
+    };
+
+    let total = match distance {
+        Some(distance) => {
+            weights.distance * distance + weights.overlap * overlap + weights.size * size + weights.appearance * appearance
+        }
+        None => f64::INFINITY,
+    };
+    AssociationCost {
+        distance,
+        overlap,
+        size,
+        appearance,
+        total,
+    }
+}
+
+/// The intersection over union of two inclusive chunk boxes, the first moved by `shift`.
+fn bounding_box_iou(moved: (Point, Point), shift: (f64, f64), fixed: (Point, Point)) -> f64 {
+    let edges = |(min, max): (Point, Point), (dx, dy): (f64, f64)| {
+        (min.x as f64 + dx, min.y as f64 + dy, (max.x + 1) as f64 + dx, (max.y + 1) as f64 + dy)
+    };
+    let a = edges(moved, shift);
+    let b = edges(fixed, (0.0, 0.0));
+    let intersection = (a.2.min(b.2) - a.0.max(b.0)).max(0.0) * (a.3.min(b.3) - a.1.max(b.1)).max(0.0);
+    let union = (a.2 - a.0) * (a.3 - a.1) + (b.2 - b.0) * (b.3 - b.1) - intersection;
+    if union > 0.0 { intersection / union } else { 0.0 }
+}
+
+/// The relative difference of two anomaly signatures, from 0.0 (equal) to 1.0.
+fn signature_difference(a: &AnomalyDetails, b: &AnomalyDetails) -> f64 {
+    let pairs = [
+        (a.luminance_score, b.luminance_score),
+        (a.color_score, b.color_score),
+        (a.hue_score, b.hue_score),
+    ];
+    let difference: f64 = pairs.iter().map(|(x, y)| (x - y).abs()).sum();
+    let magnitude: f64 = pairs.iter().map(|(x, y)| x.abs() + y.abs()).sum();
+    if magnitude > 0.0 { difference / magnitude } else { 0.0 }
+}
+
+/// Whether a blob overlaps the track's last bounding box moved to its predicted
+/// position and widened by `FRAGMENT_EXTENT_MARGIN`.
+fn overlaps_predicted_extent(tracked_blob: &TrackedBlob, blob: &SmartBlob) -> bool {
+    let last_blob = &tracked_blob.latest_blob;
+    let predicted_pos = tracked_blob.predict_next_position();
+    let (dx, dy) = (predicted_pos.0 - last_blob.center_of_mass.0, predicted_pos.1 - last_blob.center_of_mass.1);
+    let (min, max) = last_blob.bounding_box;
+    let (blob_min, blob_max) = blob.bounding_box;
+
+    min.x as f64 + dx - FRAGMENT_EXTENT_MARGIN < (blob_max.x + 1) as f64
+        && (max.x + 1) as f64 + dx + FRAGMENT_EXTENT_MARGIN > blob_min.x as f64
+        && min.y as f64 + dy - FRAGMENT_EXTENT_MARGIN < (blob_max.y + 1) as f64
+        && (max.y + 1) as f64 + dy + FRAGMENT_EXTENT_MARGIN > blob_min.y as f64
+}
+
+/// The number of empty chunks between two inclusive chunk boxes, along the axis on
+/// which they are farthest apart. 0 if they touch or overlap.
+fn box_gap(a: (Point, Point), b: (Point, Point)) -> u32 {
+    let gap_x = b.0.x.saturating_sub(a.1.x + 1).max(a.0.x.saturating_sub(b.1.x + 1));
+    let gap_y = b.0.y.saturating_sub(a.1.y + 1).max(a.0.y.saturating_sub(b.1.y + 1));
+    gap_x.max(gap_y)
+}
+
+/// Pairs each row, in order, with its lowest-cost column that is still free.
+fn greedy_assignment(costs: &[Vec<f64>]) -> Vec<Option<usize>> {
+    let mut used = HashSet::new();
+    costs
+        .iter()
+        .map(|row| {
+            let best = row
+                .iter()
+                .enumerate()
+                .filter(|(j, cost)| cost.is_finite() && !used.contains(j))
+                .min_by(|a, b| a.1.total_cmp(b.1))
+                .map(|(j, _)| j);
+            if let Some(j) = best {
+                used.insert(j);
+            }
+            best
+        })
+        .collect()
 }
 
 // --- Behavioral Anomaly Detection Helpers ---
 
-fn is_acceleration_anomalous(blob: &TrackedBlob, config: &PipelineConfig) -> bool {
-    if blob.velocity_history.len() < HISTORY_SIZE / 2 { return false; }
+// Each returns the z-score of the latest observation against the track's history, or
+// `None` while the history is too short to judge.
+
+fn acceleration_z_score(blob: &TrackedBlob, min_history: usize) -> Option<f64> {
+    if blob.velocity_history.len() < min_history { return None; }
     let (mean_vx, std_dev_vx) = calculate_vector_stats(&blob.velocity_history, |v| v.0);
     let (mean_vy, std_dev_vy) = calculate_vector_stats(&blob.velocity_history, |v| v.1);
     
     let z_score_x = (blob.velocity.0 - mean_vx) / std_dev_vx.max(0.01);
     let z_score_y = (blob.velocity.1 - mean_vy) / std_dev_vy.max(0.01);
 
-    z_score_x.abs() > config.behavioral_anomaly_threshold || z_score_y.abs() > config.behavioral_anomaly_threshold
+    Some(z_score_x.abs().max(z_score_y.abs()))
 }
 
-fn is_size_change_anomalous(blob: &TrackedBlob, config: &PipelineConfig) -> bool {
-    if blob.size_history.len() < HISTORY_SIZE / 2 { return false; }
-    let size_changes: Vec<f64> = blob.size_history.as_slices().0.windows(2).map(|w| (w[1] as f64 - w[0] as f64)).collect();
-    if size_changes.is_empty() { return false; }
+fn size_change_z_score(blob: &TrackedBlob, min_history: usize) -> Option<f64> {
+    if blob.size_history.len() < min_history { return None; }
+    let size_changes: Vec<f64> = blob.size_history.as_slices().0.windows(2).map(|w| w[1] - w[0]).collect();
+    if size_changes.is_empty() { return None; }
 
     let (mean, std_dev) = calculate_scalar_stats(&size_changes);
-    let current_change = (blob.size_history.back().unwrap() - blob.size_history.get(blob.size_history.len() - 2).unwrap()) as f64;
+    let current_change = blob.size_history.back().unwrap() - blob.size_history.get(blob.size_history.len() - 2).unwrap();
 
-    (current_change - mean) / std_dev.max(0.01) > config.behavioral_anomaly_threshold
+    Some((current_change - mean) / std_dev.max(0.01))
 }
 
-fn is_hue_change_anomalous(blob: &TrackedBlob, config: &PipelineConfig) -> bool {
-    if blob.signature_history.len() < HISTORY_SIZE / 2 { return false; }
+fn hue_change_z_score(blob: &TrackedBlob, min_history: usize) -> Option<f64> {
+    if blob.signature_history.len() < min_history { return None; }
     let hue_scores: Vec<f64> = blob.signature_history.iter().map(|s| s.hue_score).collect();
     let (mean, std_dev) = calculate_scalar_stats(&hue_scores);
     let current_hue = blob.latest_blob.average_anomaly.hue_score;
 
-    (current_hue - mean) / std_dev.max(0.01) > config.behavioral_anomaly_threshold
+    Some((current_hue - mean) / std_dev.max(0.01))
 }
 
 fn calculate_scalar_stats(data: &[f64]) -> (f64, f64) {
@@ -259,4 +1043,468 @@ fn calculate_vector_stats<F>(data: &VecDeque<(f64, f64)>, accessor: F) -> (f64,
 where F: Fn(&(f64, f64)) -> f64 {
     let values: Vec<f64> = data.iter().map(accessor).collect();
     calculate_scalar_stats(&values)
-}
\ No newline at end of file
+}
+
+#[cfg(test)]
+pub(crate) mod tests {
+    use super::*;
+    use crate::core_modules::blob_detector::blob_detector::{find_blobs, find_blobs_with, BlobDetectorConfig};
+    use crate::core_modules::moment::SceneManager;
+    use crate::core_modules::smart_chunk::ChunkStatus;
+    use crate::pipeline::tests::config;
+
+    pub(crate) const GRID_WIDTH: u32 = 64;
+    pub(crate) const GRID_HEIGHT: u32 = 80;
+
+    /// The blobs detected, the way the pipeline does, in a frame where the given chunk
+    /// rectangles are anomalous.
+    pub(crate) fn detect(rects: &[(u32, u32, u32, u32)]) -> Vec<SmartBlob> {
+        let mut status_map = vec![ChunkStatus::Stable; (GRID_WIDTH * GRID_HEIGHT) as usize];
+        for &(x, y, width, height) in rects {
+            for cy in y..y + height {
+                for cx in x..x + width {
+                    status_map[(cy * GRID_WIDTH + cx) as usize] = ChunkStatus::AnomalousEvent(AnomalyDetails {
+                        luminance_score: 5.0,
+                        color_score: 0.0,
+                        hue_score: 0.0,
+                    });
+                }
+            }
+        }
+        find_blobs(&status_map, GRID_WIDTH, GRID_HEIGHT)
+    }
+
+    /// The single blob covering the given chunk rectangles.
+    pub(crate) fn blob_of(rects: &[(u32, u32, u32, u32)]) -> SmartBlob {
+        let mut blobs = detect(rects);
+        assert_eq!(blobs.len(), 1);
+        blobs.remove(0)
+    }
+
+    /// A 2x2 chunk blob with its top-left corner at `(x, y)`.
+    pub(crate) fn square_at(x: u32, y: u32) -> SmartBlob {
+        blob_of(&[(x, y, 2, 2)])
+    }
+
+    pub(crate) fn tracker_config(assignment: TrackAssignment) -> PipelineConfig {
+        config(
+            GRID_WIDTH,
+            GRID_HEIGHT,
+            TrackerConfig { assignment, gate_distance: Distance::Pixels(20.0), ..TrackerConfig::default() },
+        )
+    }
+
+    /// The ID of the track whose latest detection has its top-left corner at `(x, y)`.
+    fn id_at(tracks: &[TrackedBlob], x: u32, y: u32) -> u64 {
+        tracks
+            .iter()
+            .find(|track| track.frames_since_seen == 0 && track.latest_blob.bounding_box.0 == Point { x, y })
+            .map(|track| track.id)
+            .expect("no track at the given position")
+    }
+
+    /// Track 0 moves right along y = 30 and slows down; track 1 moves up along x = 39
+    /// and stops just short of the prediction. On the last frame track 0's prediction
+    /// is closer to track 1's detection than to its own. Returns the IDs of the tracks
+    /// holding the two detections of the last frame.
+    fn run_crossing(assignment: TrackAssignment) -> (u64, u64) {
+        let config = tracker_config(assignment);
+        let mut tracker = Tracker::new();
+        for (a, b) in [((0, 30), (39, 66)), ((12, 30), (39, 54)), ((24, 30), (39, 42))] {
+            tracker.update(vec![square_at(a.0, a.1), square_at(b.0, b.1)], &config);
+        }
+        assert_eq!(id_at(tracker.get_tracked_blobs(), 24, 30), 0);
+        assert_eq!(id_at(tracker.get_tracked_blobs(), 39, 42), 1);
+
+        let tracks = tracker.update(vec![square_at(30, 30), square_at(39, 33)], &config);
+        (id_at(tracks, 30, 30), id_at(tracks, 39, 33))
+    }
+
+    #[test]
+    fn default_limits_match_former_constants() {
+        let limits = TrackerConfig::default().limits(&FrameGeometry::new(1280, 720, 10, 10));
+        assert_eq!(limits.history_size, 15);
+        assert_eq!(limits.new_track_frames, 5);
+        assert_eq!(limits.coast_frames, 30);
+        assert_eq!(limits.gate_distance, 5.0);
+        assert_eq!(limits.gate_growth, 0.5);
+    }
+
+    #[test]
+    fn default_config_matches_like_the_former_tracker() {
+        // 10x10 pixel chunks and the default tracker settings.
+        let config = PipelineConfig {
+            chunk_width: 10,
+            chunk_height: 10,
+            ..config(GRID_WIDTH * 10, GRID_HEIGHT * 10, TrackerConfig::default())
+        };
+        for (shift, matched) in [(0, true), (4, true), (5, false), (8, false)] {
+            let mut tracker = Tracker::new();
+            for _ in 0..2 {
+                tracker.update(vec![square_at(10, 20)], &config);
+            }
+            let tracks = tracker.update(vec![square_at(10 + shift, 20)], &config);
+            // The former tracker kept the ID within 5 chunks of the prediction.
+            assert_eq!(id_at(tracks, 10 + shift, 20) == 0, matched, "shift {shift}");
+        }
+
+        // The detection of a track is taken for `New` until its fifth frame.
+        let mut tracker = Tracker::new();
+        for frame in 1..=6 {
+            let tracks = tracker.update(vec![square_at(10, 20)], &config);
+            assert_eq!(tracks[0].state == TrackedState::New, frame < 5, "frame {frame}");
+        }
+    }
+
+    #[test]
+    fn limits_follow_frame_rate_and_geometry() {
+        let config = TrackerConfig {
+            frame_rate: 60.0,
+            gate_distance: Distance::Pixels(40.0),
+            gate_growth: Distance::Normalized(0.25),
+            ..TrackerConfig::default()
+        };
+        let limits = config.limits(&FrameGeometry::new(640, 480, 8, 8));
+        assert_eq!(limits.history_size, 30);
+        assert_eq!(limits.new_track_frames, 10);
+        assert_eq!(limits.coast_frames, 60);
+        assert_eq!(limits.gate_distance, 5.0);
+        // A quarter of the 800 pixel diagonal per second, in 8 pixel chunks per frame.
+        assert_eq!(limits.gate_growth, 200.0 / 8.0 / 60.0);
+    }
+
+    #[test]
+    fn default_weights_use_distance_alone() {
+        let config = tracker_config(TrackAssignment::Hungarian);
+        let mut tracker = Tracker::new();
+        tracker.update(vec![square_at(10, 10)], &config);
+        tracker.update(vec![blob_of(&[(12, 10, 4, 3)])], &config);
+        let candidate = &tracker.get_association_diagnostics()[0];
+        assert!(candidate.cost.size > 0.0);
+        assert_eq!(Some(candidate.cost.total), candidate.cost.distance);
+    }
+
+    #[test]
+    fn greedy_assignment_swaps_crossing_tracks() {
+        assert_eq!(run_crossing(TrackAssignment::Greedy), (1, 0));
+    }
+
+    #[test]
+    fn hungarian_assignment_keeps_crossing_tracks() {
+        assert_eq!(run_crossing(TrackAssignment::Hungarian), (0, 1));
+    }
+
+    #[test]
+    fn hungarian_assignment_reports_the_chosen_pairs() {
+        let config = tracker_config(TrackAssignment::Hungarian);
+        let mut tracker = Tracker::new();
+        for (a, b) in [((0, 30), (39, 66)), ((12, 30), (39, 54)), ((24, 30), (39, 42)), ((30, 30), (39, 33))] {
+            tracker.update(vec![square_at(a.0, a.1), square_at(b.0, b.1)], &config);
+        }
+        let matched: Vec<(u64, usize)> = tracker
+            .get_association_diagnostics()
+            .iter()
+            .filter(|candidate| candidate.matched)
+            .map(|candidate| (candidate.track_id, candidate.detection_index))
+            .collect();
+        assert_eq!(matched, vec![(0, 0), (1, 1)]);
+
+        // Track 0's prediction is nearer the other detection, but that pairing costs more overall.
+        let cost = |track_id, detection_index| {
+            tracker
+                .get_association_diagnostics()
+                .iter()
+                .find(|c| c.track_id == track_id && c.detection_index == detection_index)
+                .map(|c| c.cost.total)
+                .unwrap()
+        };
+        assert!(cost(0, 1) < cost(0, 0));
+        assert!(cost(0, 0) + cost(1, 1) < cost(0, 1) + cost(1, 0));
+    }
+
+    /// A person moving right: a 2x2 head over a 4x5 torso, with a one-row gap between
+    /// them when `fragmented`.
+    fn person_at(x: u32, fragmented: bool) -> Vec<(u32, u32, u32, u32)> {
+        let torso_top = if fragmented { 13 } else { 12 };
+        vec![(x + 1, 10, 2, 2), (x, torso_top, 4, 18 - torso_top)]
+    }
+
+    #[test]
+    fn fragments_within_predicted_extent_form_one_moment() {
+        let config = tracker_config(TrackAssignment::Hungarian);
+        let mut scene = SceneManager::new();
+        for frame in 0..6u32 {
+            let blobs = detect(&person_at(5 + 2 * frame, frame >= 2));
+            assert_eq!(blobs.len(), if frame >= 2 { 2 } else { 1 });
+            scene.update(blobs, &config);
+
+            let tracks = scene.get_tracked_blobs();
+            assert_eq!(tracks.len(), 1);
+            assert_eq!(tracks[0].id, 0);
+            assert_eq!(tracks[0].frames_since_seen, 0);
+            assert_eq!(tracks[0].latest_blob.size_in_chunks, if frame >= 2 { 24 } else { 28 });
+        }
+
+        assert!(scene.get_completed_moments().is_empty());
+        let moments = scene.get_active_moments();
+        assert_eq!(moments.len(), 1);
+        assert_eq!((moments[0].start_frame, moments[0].end_frame), (1, 6));
+        assert_eq!(moments[0].blob_history.len(), 6);
+    }
+
+    #[test]
+    fn fragments_outside_predicted_extent_stay_separate() {
+        let config = tracker_config(TrackAssignment::Hungarian);
+        let mut tracker = Tracker::new();
+        for frame in 0..2u32 {
+            tracker.update(detect(&person_at(5 + 2 * frame, false)), &config);
+        }
+
+        // Two empty rows below the torso: close to the person, but beyond the margin of
+        // its predicted extent.
+        let mut rects = person_at(9, false);
+        rects.push((9, 20, 4, 2));
+        let blobs = detect(&rects);
+        assert_eq!(blobs.len(), 2);
+        let tracks = tracker.update(blobs, &config);
+
+        assert_eq!(tracks.len(), 2);
+        assert_eq!(id_at(tracks, 9, 10), 0);
+        assert_eq!(id_at(tracks, 9, 20), 1);
+        assert_eq!(tracks[0].latest_blob.size_in_chunks, 28);
+    }
+
+    #[test]
+    fn fragment_fitting_two_tracks_is_merged_into_one() {
+        let config = tracker_config(TrackAssignment::Hungarian);
+        let mut tracker = Tracker::new();
+        let left = (10, 20, 4, 4);
+        let right = (17, 20, 4, 4);
+        for _ in 0..2 {
+            tracker.update(detect(&[left, right]), &config);
+        }
+
+        // Touches both squares only diagonally, and lies within both predicted extents.
+        let fragment = (14, 24, 3, 2);
+        let blobs = detect(&[left, right, fragment]);
+        assert_eq!(blobs.len(), 3);
+        let tracks = tracker.update(blobs, &config);
+
+        assert_eq!(tracks.len(), 2);
+        assert!(tracks.iter().all(|track| track.frames_since_seen == 0));
+        let owners = tracks
+            .iter()
+            .filter(|track| track.latest_blob.chunk_coords.contains(&Point { x: 15, y: 24 }))
+            .count();
+        assert_eq!(owners, 1);
+        let total: usize = tracks.iter().map(|track| track.latest_blob.size_in_chunks).sum();
+        assert_eq!(total, 16 + 16 + 6);
+    }
+
+    /// Runs the tracker over scripted frames, each a list of chunk rectangles, and
+    /// collects every event emitted.
+    fn events_of(frames: &[Vec<(u32, u32, u32, u32)>]) -> Vec<TrackEvent> {
+        let config = tracker_config(TrackAssignment::Hungarian);
+        let mut tracker = Tracker::new();
+        let mut events = Vec::new();
+        for rects in frames {
+            tracker.update(detect(rects), &config);
+            events.extend_from_slice(tracker.get_track_events());
+        }
+        events
+    }
+
+    fn event(track_id: u64, frame: u64, kind: TrackEventKind) -> TrackEvent {
+        TrackEvent { track_id, frame, kind }
+    }
+
+    #[test]
+    fn events_follow_a_track_through_its_life() {
+        let square = vec![(10, 20, 2, 2)];
+        let mut frames = vec![square.clone(); 6];
+        frames.extend([Vec::new(), Vec::new(), square]);
+        // Unseen from frame 10; the 30-frame coast window runs out on frame 40.
+        frames.extend(vec![Vec::new(); 31]);
+
+        assert_eq!(
+            events_of(&frames),
+            vec![
+                event(0, 1, TrackEventKind::Started),
+                event(0, 5, TrackEventKind::Confirmed),
+                event(0, 7, TrackEventKind::Lost),
+                event(0, 9, TrackEventKind::Recovered { frames_unseen: 2 }),
+                event(0, 10, TrackEventKind::Lost),
+                event(0, 40, TrackEventKind::Ended),
+            ]
+        );
+    }
+
+    #[test]
+    fn events_report_a_split_after_the_start_of_the_child() {
+        let whole = vec![(10, 20, 8, 4)];
+        let frames = vec![whole.clone(), whole, vec![(10, 20, 2, 4), (15, 20, 3, 4)]];
+        assert_eq!(
+            events_of(&frames),
+            vec![
+                event(0, 1, TrackEventKind::Started),
+                event(1, 3, TrackEventKind::Started),
+                event(1, 3, TrackEventKind::Split { parent_id: 0 }),
+            ]
+        );
+    }
+
+    #[test]
+    fn events_report_a_merge_before_the_end_of_the_absorbed_track() {
+        let apart = vec![(10, 20, 3, 4), (16, 20, 3, 4)];
+        let frames = vec![apart.clone(), apart, vec![(10, 20, 9, 4)]];
+        let events = events_of(&frames);
+        assert_eq!(events[..2], [event(0, 1, TrackEventKind::Started), event(1, 1, TrackEventKind::Started)]);
+
+        let absorbed_id = events[2].track_id;
+        let survivor_id = 1 - absorbed_id;
+        assert_eq!(
+            events[2..],
+            [
+                event(absorbed_id, 3, TrackEventKind::Merged { into_id: survivor_id }),
+                event(absorbed_id, 3, TrackEventKind::Ended),
+            ]
+        );
+    }
+
+    #[test]
+    fn events_order_matched_before_lost_before_started() {
+        let config = tracker_config(TrackAssignment::Hungarian);
+        let mut tracker = Tracker::new();
+        for _ in 0..4 {
+            tracker.update(detect(&[(10, 20, 2, 2), (40, 20, 2, 2)]), &config);
+        }
+        // Track 0 is confirmed, track 1 disappears and a new object appears far away.
+        tracker.update(detect(&[(10, 20, 2, 2), (30, 60, 2, 2)]), &config);
+        assert_eq!(
+            tracker.get_track_events(),
+            &vec![
+                event(0, 5, TrackEventKind::Confirmed),
+                event(1, 5, TrackEventKind::Lost),
+                event(2, 5, TrackEventKind::Started),
+            ]
+        );
+    }
+
+    /// Tracks a single object through the given detections with the default tracker
+    /// settings and returns the track after the last frame.
+    fn track_through(blobs: Vec<SmartBlob>, tracker_config: TrackerConfig) -> TrackedBlob {
+        let config = config(GRID_WIDTH, GRID_HEIGHT, tracker_config);
+        let mut tracker = Tracker::new();
+        for blob in blobs {
+            tracker.update(vec![blob], &config);
+        }
+        assert_eq!(tracker.get_tracked_blobs().len(), 1);
+        tracker.get_tracked_blobs()[0].clone()
+    }
+
+    fn reason_kinds(track: &TrackedBlob) -> Vec<AnomalyKind> {
+        track.anomaly_reasons.iter().map(|reason| reason.kind).collect()
+    }
+
+    /// Twenty frames of a 2x2 square moving one chunk right per frame, which fills the
+    /// track's history with identical velocities, sizes and hues.
+    fn steady_square() -> Vec<SmartBlob> {
+        (0..20).map(|frame| square_at(10 + frame, 30)).collect()
+    }
+
+    #[test]
+    fn steady_track_has_no_anomaly_reasons() {
+        let track = track_through(steady_square(), TrackerConfig::default());
+        assert_eq!(track.state, TrackedState::Tracking);
+        assert!(track.anomaly_reasons.is_empty());
+    }
+
+    #[test]
+    fn sudden_speed_change_is_acceleration() {
+        let mut blobs = steady_square();
+        // Five chunks instead of one: still inside the gate of the predicted position.
+        blobs.push(square_at(34, 30));
+        let track = track_through(blobs, TrackerConfig::default());
+        assert_eq!(track.state, TrackedState::Anomalous);
+        assert_eq!(reason_kinds(&track), [AnomalyKind::Acceleration]);
+        assert_eq!(track.anomaly_reasons[0].threshold, 3.0);
+    }
+
+    #[test]
+    fn sudden_growth_is_size_change() {
+        let mut blobs: Vec<SmartBlob> = (0..20).map(|_| square_at(10, 30)).collect();
+        // A 4x4 square around the same center, so the object grows without moving.
+        blobs.push(blob_of(&[(9, 29, 4, 4)]));
+        let track = track_through(blobs, TrackerConfig::default());
+        assert_eq!(track.state, TrackedState::Anomalous);
+        assert_eq!(reason_kinds(&track), [AnomalyKind::SizeChange]);
+    }
+
+    #[test]
+    fn rising_hue_score_is_hue_change() {
+        let mut blobs = steady_square();
+        let mut changed = square_at(30, 30);
+        changed.average_anomaly.hue_score = 4.0;
+        blobs.push(changed);
+        let track = track_through(blobs, TrackerConfig::default());
+        assert_eq!(track.state, TrackedState::Anomalous);
+        assert_eq!(reason_kinds(&track), [AnomalyKind::HueChange]);
+    }
+
+    #[test]
+    fn anomaly_threshold_comes_from_the_tracker_config() {
+        let mut blobs = steady_square();
+        blobs.push(square_at(34, 30));
+        let lenient = TrackerConfig { behavioral_anomaly_threshold: 10.0, ..TrackerConfig::default() };
+        let track = track_through(blobs, lenient);
+        assert_eq!(track.state, TrackedState::Tracking);
+        assert!(track.anomaly_reasons.is_empty());
+    }
+
+    /// A 4x4 object with its top-left corner at `(x, y)` whose leading (rightmost)
+    /// `anomalous_columns` are anomalous and whose trailing columns have settled into
+    /// predictable motion, detected with predictable chunks joining the blob.
+    fn moving_object(x: u32, y: u32, anomalous_columns: u32) -> SmartBlob {
+        let mut status_map = vec![ChunkStatus::Stable; (GRID_WIDTH * GRID_HEIGHT) as usize];
+        for cy in y..y + 4 {
+            for cx in x..x + 4 {
+                status_map[(cy * GRID_WIDTH + cx) as usize] = if cx >= x + 4 - anomalous_columns {
+                    ChunkStatus::AnomalousEvent(AnomalyDetails { luminance_score: 5.0, color_score: 0.0, hue_score: 0.0 })
+                } else {
+                    ChunkStatus::PredictableMotion
+                };
+            }
+        }
+        let config = BlobDetectorConfig { predictable_motion_weight: Some(1.0), ..BlobDetectorConfig::default() };
+        let mut blobs = find_blobs_with(&status_map, None, GRID_WIDTH, GRID_HEIGHT, &config);
+        assert_eq!(blobs.len(), 1);
+        blobs.remove(0)
+    }
+
+    /// Twenty frames of the object moving one chunk right per frame, with one or two
+    /// of its columns anomalous in turn.
+    fn smoothly_moving_object() -> Vec<SmartBlob> {
+        (0..20).map(|frame| moving_object(10 + frame, 30, 1 + frame % 2)).collect()
+    }
+
+    #[test]
+    fn smoothly_moving_object_with_predictable_chunks_is_not_flagged() {
+        let blobs = smoothly_moving_object();
+        assert!(blobs.iter().all(|blob| blob.size_in_chunks == 16 && blob.predictable_chunk_count >= 8));
+        let track = track_through(blobs, TrackerConfig::default());
+        assert_eq!(track.state, TrackedState::Tracking);
+        assert!(track.anomaly_reasons.is_empty());
+    }
+
+    #[test]
+    fn sudden_turn_or_speed_change_of_a_moving_object_is_flagged() {
+        // The last position of the smooth path is (29, 30).
+        for (x, y) in [(29, 33), (34, 30)] {
+            let mut blobs = smoothly_moving_object();
+            blobs.push(moving_object(x, y, 1));
+            let track = track_through(blobs, TrackerConfig::default());
+            assert_eq!(track.state, TrackedState::Anomalous, "to ({x}, {y})");
+            assert_eq!(reason_kinds(&track), [AnomalyKind::Acceleration], "to ({x}, {y})");
+        }
+    }
+}
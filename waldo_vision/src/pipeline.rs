@@ -3,17 +3,52 @@
 // It encapsulates the full architectural stack into a single, easy-to-use interface.
 // Its purpose is to provide a clean and user-friendly entry point for processing
 // image data and receiving high-level, actionable reports about significant events.
+//
+// Frames can be supplied as raw buffers or as `image` crate types. Raw buffers are the
+// fastest path and are trusted to match the configured geometry; `image` types are
+// checked against `PipelineConfig` and converted only when their layout differs from
+// what the grid expects. Compressed JPEG/PNG bytes are decoded into a buffer owned by
+// the pipeline and reused from frame to frame. Blobs that survive filtering are given
+// a color histogram of the frame pixels beneath them and, when enabled, a pixel-accurate
+// foreground mask. Output geometry is in grid units; every `FrameAnalysis` carries the
+// `FrameGeometry` needed to convert it to pixel or normalized coordinates.
 
 use crate::core_modules::blob_detector::blob_detector;
-use crate::core_modules::grid_manager::GridManager;
+use crate::core_modules::blob_filter::{self, FilterContext};
+use crate::core_modules::frame_decoder::FrameDecoder;
+use crate::core_modules::grid_manager::{FrameView, GridManager};
 use crate::core_modules::moment::SceneManager;
-use crate::core_modules::smart_blob::SmartBlob;
+use crate::core_modules::morphology;
+use crate::core_modules::pixel::pixel::{Channel16, LuminanceModel};
+use image::{DynamicImage, RgbaImage};
 use std::collections::VecDeque;
+use std::fmt;
+use std::sync::Arc;
 
 // Re-export key data structures for the public API.
+pub use crate::core_modules::appearance::ColorHistogram;
 pub use crate::core_modules::moment::Moment;
+pub use crate::core_modules::perspective::{PerspectiveError, PerspectiveModel, ReferenceSize};
+pub use crate::core_modules::morphology::{MorphologyConfig, MorphologyOperation, StructuringElement};
+pub use crate::core_modules::blob_filter::{
+    default_blob_filters, AspectRatioFilter, BlobFilter, BlobRejection, EdgeTouchFilter, MaxSizeFilter,
+    MinAnomalyScoreFilter, MinSizeFilter, SizeOutlierFilter, ZoneFilter, ZoneMode, DEFAULT_MIN_BLOB_SIZE,
+    DEFAULT_SIZE_OUTLIER_STD_DEVS,
+};
+pub use crate::core_modules::blob_detector::blob_detector::{BlobAlgorithm, BlobDetectorConfig, Connectivity, HeatFunction};
+pub use crate::core_modules::frame_decoder::{DecodeError, JpegScale};
+pub use crate::core_modules::geometry::{
+    Distance, FrameGeometry, GridPoint, NormalizedBox, NormalizedPoint, PixelBox, PixelPoint,
+};
+pub use crate::core_modules::smart_blob::{Point, SmartBlob};
+pub use crate::core_modules::grid_manager::{BayerPattern, ForegroundRefinement, Frame16Layout};
+pub use crate::core_modules::pixel::pixel::{BitDepth, LuminanceStandard};
 pub use crate::core_modules::smart_chunk::{AnomalyDetails, ChunkStatus};
-pub use crate::core_modules::tracker::{TrackedBlob, TrackedState};
+pub use crate::core_modules::kalman::{KalmanConfig, KalmanFilter};
+pub use crate::core_modules::tracker::{
+    AnomalyKind, AnomalyReason, AssociationCandidate, AssociationCost, AssociationWeights, LineageEvent, MotionModel, TrackAssignment,
+    TrackEvent, TrackEventKind, TrackRecovered, TrackedBlob, TrackedState, TrackerConfig, TrackerConfigError,
+};
 
 const BLOB_SIZE_HISTORY_LENGTH: usize = 100;
 const SCENE_STABILITY_HISTORY_LENGTH: usize = 30;
@@ -27,6 +62,45 @@ pub enum SceneState {
     Disturbed,
 }
 
+/// The ways in which the pipeline can refuse a frame.
+#[derive(Debug, Clone, PartialEq)]
+pub enum PipelineError {
+    /// The frame's dimensions do not match `image_width` x `image_height`.
+    DimensionMismatch { expected: (u32, u32), actual: (u32, u32) },
+    /// An encoded frame could not be decoded.
+    Decode(DecodeError),
+    /// The tracker settings are invalid.
+    InvalidTrackerConfig(TrackerConfigError),
+}
+
+impl From<DecodeError> for PipelineError {
+    fn from(error: DecodeError) -> Self {
+        PipelineError::Decode(error)
+    }
+}
+
+impl From<TrackerConfigError> for PipelineError {
+    fn from(error: TrackerConfigError) -> Self {
+        PipelineError::InvalidTrackerConfig(error)
+    }
+}
+
+impl fmt::Display for PipelineError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            PipelineError::DimensionMismatch { expected, actual } => write!(
+                f,
+                "frame is {}x{} but the pipeline is configured for {}x{}",
+                actual.0, actual.1, expected.0, expected.1
+            ),
+            PipelineError::Decode(error) => write!(f, "{}", error),
+            PipelineError::InvalidTrackerConfig(error) => write!(f, "invalid tracker config: {}", error),
+        }
+    }
+}
+
+impl std::error::Error for PipelineError {}
+
 /// Configuration for the VisionPipeline, allowing for tunable behavior.
 #[derive(Debug, Clone)]
 pub struct PipelineConfig {
@@ -34,15 +108,51 @@ pub struct PipelineConfig {
     pub image_height: u32,
     pub chunk_width: u32,
     pub chunk_height: u32,
-    pub new_age_threshold: u32,
-    pub behavioral_anomaly_threshold: f64,
-    pub absolute_min_blob_size: usize,
-    pub blob_size_std_dev_filter: f64,
+    pub luminance_standard: LuminanceStandard,
+    pub linear_light: bool,
+    /// The significant bits of raw 16-bit buffers given to `process_frame16` and the
+    /// high bit-depth Bayer path. `image` crate frames are always read as full 16-bit.
+    pub high_bit_depth: BitDepth,
+    /// The resolution at which encoded JPEG frames are decoded. With a reduced scale,
+    /// `image_width` and `image_height` must describe the scaled frame.
+    pub jpeg_decode_scale: JpegScale,
+    /// Binary morphology applied to the anomaly mask before blob detection.
+    pub morphology: MorphologyConfig,
+    pub blob_algorithm: BlobAlgorithm,
+    /// How anomalous chunks are scored on the blob heatmap.
+    pub heat_function: HeatFunction,
+    /// The minimum heat for a chunk to seed a blob.
+    pub peak_threshold: f64,
+    /// The minimum heat for a chunk to join a growing blob.
+    pub growth_threshold: f64,
+    /// If set, `PredictableMotion` chunks extend blobs grown from anomalous seeds,
+    /// contributing this heat each.
+    pub predictable_motion_weight: Option<f64>,
+    /// If set, surviving blobs get a pixel-accurate foreground mask and tight pixel bounds.
+    pub foreground_refinement: Option<ForegroundRefinement>,
+    /// Settings of the object tracker.
+    pub tracker: TrackerConfig,
+    /// Maps blob positions to the size of a reference object there. Sizes used for
+    /// filtering and tracking are normalized by it.
+    pub perspective: PerspectiveModel,
+    /// The filters run, in order, on every detected blob. The first filter to reject a
+    /// blob drops it. `default_blob_filters()` gives the standard minimum size and size
+    /// outlier rules (sizes are in chunks when the perspective is uniform). These replace
+    /// the former `absolute_min_blob_size` and `blob_size_std_dev_filter` settings, which
+    /// map onto `MinSizeFilter` and `SizeOutlierFilter`.
+    pub blob_filters: Vec<Arc<dyn BlobFilter>>,
     pub disturbance_entry_threshold: f64,
     pub disturbance_exit_threshold: f64,
     pub disturbance_confirmation_frames: u32,
 }
 
+impl PipelineConfig {
+    /// The image and chunk geometry described by this config.
+    pub fn geometry(&self) -> FrameGeometry {
+        FrameGeometry::new(self.image_width, self.image_height, self.chunk_width, self.chunk_height)
+    }
+}
+
 /// The detailed data package for a significant event.
 #[derive(Debug, Clone)]
 pub struct MentionData {
@@ -63,7 +173,21 @@ pub enum Report {
 pub struct FrameAnalysis {
     pub report: Report,
     pub status_map: Vec<ChunkStatus>,
+    /// The per-chunk anomaly mask after morphological cleanup, as used for blob detection.
+    pub cleaned_mask: Vec<bool>,
+    /// The per-chunk heat used for blob detection, in the same order as `status_map`.
+    pub heatmap: Vec<f64>,
+    /// The blobs dropped by the filter chain this frame, and why.
+    pub rejected_blobs: Vec<BlobRejection>,
     pub tracked_blobs: Vec<TrackedBlob>,
+    /// The cost breakdown of every track-detection pair considered this frame.
+    pub association_diagnostics: Vec<AssociationCandidate>,
+    /// The tracks re-acquired this frame after going unseen.
+    pub recovered_tracks: Vec<TrackRecovered>,
+    /// The lifecycle events of the tracks this frame, in the order they happened.
+    pub track_events: Vec<TrackEvent>,
+    /// The geometry for converting this frame's grid coordinates to pixels or 0..1 units.
+    pub geometry: FrameGeometry,
     pub scene_state: SceneState,
     pub significant_event_count: u64,
 }
@@ -71,43 +195,185 @@ pub struct FrameAnalysis {
 /// The main, top-level struct for the vision engine.
 pub struct VisionPipeline {
     grid_manager: GridManager,
+    frame_decoder: FrameDecoder,
     scene_manager: SceneManager,
     config: PipelineConfig,
-    blob_size_history: VecDeque<usize>,
+    blob_size_history: VecDeque<f64>,
     significant_event_count: u64,
     scene_state: SceneState,
     frames_in_current_state: u32,
 }
 
 impl VisionPipeline {
+    /// Creates a pipeline.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `config.tracker` fails `TrackerConfig::validate`, for example with a
+    /// zero frame rate or a negative duration. Configs built from `TrackerConfig::default()`
+    /// never panic. Callers that build the tracker settings from user input should use
+    /// `try_new`, which returns the same problem as a `PipelineError` instead.
     pub fn new(config: PipelineConfig) -> Self {
-        let grid_manager = GridManager::new(
+        match Self::try_new(config) {
+            Ok(pipeline) => pipeline,
+            Err(error) => panic!("{}", error),
+        }
+    }
+
+    /// Creates a pipeline, or reports why the config cannot be used.
+    pub fn try_new(config: PipelineConfig) -> Result<Self, PipelineError> {
+        config.tracker.validate()?;
+        let grid_manager = GridManager::with_luminance_model(
             config.image_width,
             config.image_height,
             config.chunk_width,
             config.chunk_height,
+            LuminanceModel::new(config.luminance_standard, config.linear_light),
         );
-        Self {
+        Ok(Self {
             grid_manager,
+            frame_decoder: FrameDecoder::new(),
             scene_manager: SceneManager::new(),
             config,
             blob_size_history: VecDeque::with_capacity(BLOB_SIZE_HISTORY_LENGTH),
             significant_event_count: 0,
             scene_state: SceneState::Calibrating,
             frames_in_current_state: 0,
-        }
+        })
     }
 
     pub fn process_frame(&mut self, frame_buffer: &[u8]) -> FrameAnalysis {
-        let status_map = self.grid_manager.process_frame(frame_buffer);
+        self.process_view(FrameView::Rgba8(frame_buffer))
+    }
+
+    /// Processes a frame of 16-bit samples, such as Gray16 thermal or RGB48 HDR data.
+    /// The samples are interpreted with the `high_bit_depth` from the config.
+    pub fn process_frame16(&mut self, frame_buffer: &[Channel16], layout: Frame16Layout) -> FrameAnalysis {
+        self.process_view(FrameView::Samples16 {
+            samples: frame_buffer,
+            layout,
+            bit_depth: self.config.high_bit_depth,
+        })
+    }
+
+    /// Processes 16-bit samples known to use the full 16-bit range.
+    fn process_full_range16(&mut self, frame_buffer: &[Channel16], layout: Frame16Layout) -> FrameAnalysis {
+        self.process_view(FrameView::Samples16 {
+            samples: frame_buffer,
+            layout,
+            bit_depth: BitDepth::Sixteen,
+        })
+    }
+
+    /// Processes an 8-bit raw Bayer frame straight from the sensor, without demosaicing.
+    pub fn process_bayer_frame(&mut self, frame_buffer: &[u8], pattern: BayerPattern) -> FrameAnalysis {
+        self.process_view(FrameView::Bayer8 { samples: frame_buffer, pattern })
+    }
+
+    /// Processes a high bit-depth raw Bayer frame, interpreted with `high_bit_depth`.
+    pub fn process_bayer_frame16(&mut self, frame_buffer: &[Channel16], pattern: BayerPattern) -> FrameAnalysis {
+        self.process_view(FrameView::Bayer16 {
+            samples: frame_buffer,
+            pattern,
+            bit_depth: self.config.high_bit_depth,
+        })
+    }
+
+    /// Processes an `image` crate RGBA frame. The pixel data is borrowed, not copied.
+    pub fn process_rgba_image(&mut self, image: &RgbaImage) -> Result<FrameAnalysis, PipelineError> {
+        self.check_dimensions(image.width(), image.height())?;
+        Ok(self.process_frame(image.as_raw()))
+    }
+
+    /// Processes any `image` crate frame. RGBA8 data is borrowed directly, 16-bit
+    /// data goes through the high bit-depth path, and every other format is
+    /// converted to RGBA8 first. `image` 16-bit buffers always span the full 0-65535
+    /// range, so they are read as 16-bit regardless of `high_bit_depth`.
+    pub fn process_image(&mut self, image: &DynamicImage) -> Result<FrameAnalysis, PipelineError> {
+        self.check_dimensions(image.width(), image.height())?;
+        let analysis = match image {
+            DynamicImage::ImageRgba8(rgba) => self.process_frame(rgba.as_raw()),
+            DynamicImage::ImageLuma16(gray) => self.process_full_range16(gray.as_raw(), Frame16Layout::Gray16),
+            DynamicImage::ImageRgb16(rgb) => self.process_full_range16(rgb.as_raw(), Frame16Layout::Rgb48),
+            DynamicImage::ImageRgba16(rgba) => self.process_full_range16(rgba.as_raw(), Frame16Layout::Rgba64),
+            other => self.process_frame(other.to_rgba8().as_raw()),
+        };
+        Ok(analysis)
+    }
+
+    /// Processes a compressed JPEG (including MJPEG frames) or PNG frame. The format is
+    /// detected from the data, and JPEGs are decoded at `jpeg_decode_scale`.
+    pub fn process_encoded_frame(&mut self, bytes: &[u8]) -> Result<FrameAnalysis, PipelineError> {
+        // The decoder is taken out for the duration of the frame so its buffer can be
+        // borrowed while the rest of the pipeline is updated.
+        let mut frame_decoder = std::mem::take(&mut self.frame_decoder);
+        let analysis = match frame_decoder.decode(bytes, self.config.jpeg_decode_scale) {
+            Ok((width, height, rgba)) => self
+                .check_dimensions(width, height)
+                .map(|()| self.process_view(FrameView::Rgba8(rgba))),
+            Err(error) => Err(error.into()),
+        };
+        self.frame_decoder = frame_decoder;
+        analysis
+    }
+
+    fn process_view(&mut self, frame: FrameView) -> FrameAnalysis {
+        let status_map = self.grid_manager.process_view(frame);
+        self.analyze_status_map(status_map, &frame)
+    }
+
+    fn check_dimensions(&self, width: u32, height: u32) -> Result<(), PipelineError> {
+        let expected = (self.config.image_width, self.config.image_height);
+        if (width, height) != expected {
+            return Err(PipelineError::DimensionMismatch {
+                expected,
+                actual: (width, height),
+            });
+        }
+        Ok(())
+    }
+
+    fn analyze_status_map(&mut self, status_map: Vec<ChunkStatus>, frame: &FrameView) -> FrameAnalysis {
         self.analyze_scene_stability(&status_map);
 
-        let raw_blobs = blob_detector::find_blobs(
+        let grid_width = self.config.image_width / self.config.chunk_width;
+        let grid_height = self.config.image_height / self.config.chunk_height;
+        let cleaned_mask = morphology::apply(
+            &morphology::anomaly_mask(&status_map),
+            grid_width,
+            grid_height,
+            &self.config.morphology,
+        );
+
+        let (raw_blobs, heatmap) = blob_detector::find_blobs_with_heatmap(
             &status_map,
-            self.config.image_width / self.config.chunk_width,
-            self.config.image_height / self.config.chunk_height,
+            Some(&cleaned_mask),
+            grid_width,
+            grid_height,
+            &BlobDetectorConfig {
+                algorithm: self.config.blob_algorithm,
+                heat_function: self.config.heat_function.clone(),
+                peak_threshold: self.config.peak_threshold,
+                growth_threshold: self.config.growth_threshold,
+                predictable_motion_weight: self.config.predictable_motion_weight,
+            },
         );
-        let filtered_blobs = self.filter_blobs(raw_blobs);
+        let (mut filtered_blobs, rejected_blobs) = self.filter_blobs(raw_blobs);
+        for blob in &mut filtered_blobs {
+            blob.appearance = Some(ColorHistogram::from_chunks(
+                frame,
+                &blob.chunk_coords,
+                self.config.image_width,
+                self.config.chunk_width,
+                self.config.chunk_height,
+            ));
+            if let Some(refinement) = &self.config.foreground_refinement {
+                blob.foreground = Some(self.grid_manager.foreground_mask(frame, &blob.chunk_coords, refinement));
+            }
+        }
+        if self.config.foreground_refinement.is_some() {
+            self.grid_manager.remember_pixels(frame);
+        }
         let (newly_started, newly_completed) = self.scene_manager.update(filtered_blobs, &self.config);
 
         let new_significant_moments: Vec<Moment> = newly_started.into_iter().filter(|m| m.is_significant).collect();
@@ -130,42 +396,58 @@ impl VisionPipeline {
 
         FrameAnalysis {
             report,
-            status_map: status_map.to_vec(),
+            status_map,
+            cleaned_mask,
+            heatmap,
+            rejected_blobs,
             tracked_blobs: self.scene_manager.get_tracked_blobs().to_vec(),
+            association_diagnostics: self.scene_manager.get_association_diagnostics().to_vec(),
+            recovered_tracks: self.scene_manager.get_recovered_tracks().to_vec(),
+            track_events: self.scene_manager.get_track_events().to_vec(),
+            geometry: self.config.geometry(),
             scene_state: self.scene_state.clone(),
             significant_event_count: self.significant_event_count,
         }
     }
 
-    fn filter_blobs(&mut self, blobs: Vec<SmartBlob>) -> Vec<SmartBlob> {
-        let (mean, std_dev) = {
-            if self.blob_size_history.is_empty() { (0.0, 0.0) } else {
-                let sum: usize = self.blob_size_history.iter().sum();
-                let mean = sum as f64 / self.blob_size_history.len() as f64;
-                let variance = self.blob_size_history.iter()
-                    .map(|value| (*value as f64 - mean).powi(2))
-                    .sum::<f64>() / self.blob_size_history.len() as f64;
-                (mean, variance.sqrt())
-            }
+    /// Runs every blob through the filter chain, returning the kept blobs and the
+    /// records of the rejected ones.
+    fn filter_blobs(&mut self, blobs: Vec<SmartBlob>) -> (Vec<SmartBlob>, Vec<BlobRejection>) {
+        // The size outlier rule only applies once enough size history has been collected.
+        let size_statistics = if self.blob_size_history.len() >= BLOB_SIZE_HISTORY_LENGTH / 2 {
+            let sum: f64 = self.blob_size_history.iter().sum();
+            let mean = sum / self.blob_size_history.len() as f64;
+            let variance = self.blob_size_history.iter()
+                .map(|value| (value - mean).powi(2))
+                .sum::<f64>() / self.blob_size_history.len() as f64;
+            Some((mean, variance.sqrt()))
+        } else {
+            None
+        };
+        let context = FilterContext {
+            geometry: self.config.geometry(),
+            size_statistics,
         };
 
+        let chain = || self.config.blob_filters.iter().map(|filter| filter.as_ref());
+
         let mut filtered_blobs = Vec::new();
-        for blob in blobs {
-            if blob.size_in_chunks < self.config.absolute_min_blob_size { continue; }
-            if self.blob_size_history.len() >= BLOB_SIZE_HISTORY_LENGTH / 2 {
-                let threshold = mean - self.config.blob_size_std_dev_filter * std_dev;
-                if (blob.size_in_chunks as f64) < threshold { continue; }
+        let mut rejected_blobs = Vec::new();
+        for mut blob in blobs {
+            blob.normalized_size = self.config.perspective.normalized_size(&blob);
+            match blob_filter::apply_chain(chain(), &blob, &context) {
+                Some(rejection) => rejected_blobs.push(rejection),
+                None => filtered_blobs.push(blob),
             }
-            filtered_blobs.push(blob);
         }
 
         for blob in &filtered_blobs {
             if self.blob_size_history.len() >= BLOB_SIZE_HISTORY_LENGTH {
                 self.blob_size_history.pop_front();
             }
-            self.blob_size_history.push_back(blob.size_in_chunks);
+            self.blob_size_history.push_back(blob.normalized_size);
         }
-        filtered_blobs
+        (filtered_blobs, rejected_blobs)
     }
 
     fn analyze_scene_stability(&mut self, status_map: &[ChunkStatus]) {
@@ -210,3 +492,197 @@ impl VisionPipeline {
         self.frames_in_current_state = 0;
     }
 }
+
+#[cfg(test)]
+pub(crate) mod tests {
+    use super::*;
+
+    /// A config with one-pixel chunks, so grid and pixel coordinates coincide.
+    pub(crate) fn config(image_width: u32, image_height: u32, tracker: TrackerConfig) -> PipelineConfig {
+        PipelineConfig {
+            image_width,
+            image_height,
+            chunk_width: 1,
+            chunk_height: 1,
+            luminance_standard: LuminanceStandard::default(),
+            linear_light: false,
+            high_bit_depth: BitDepth::default(),
+            jpeg_decode_scale: JpegScale::default(),
+            morphology: MorphologyConfig::default(),
+            blob_algorithm: BlobAlgorithm::default(),
+            heat_function: HeatFunction::default(),
+            peak_threshold: 1.0,
+            growth_threshold: 1.0,
+            predictable_motion_weight: None,
+            foreground_refinement: None,
+            tracker,
+            perspective: PerspectiveModel::Uniform,
+            blob_filters: default_blob_filters(),
+            disturbance_entry_threshold: 0.25,
+            disturbance_exit_threshold: 0.15,
+            disturbance_confirmation_frames: 5,
+        }
+    }
+
+    fn try_new_error(tracker: TrackerConfig) -> Option<PipelineError> {
+        VisionPipeline::try_new(config(64, 48, tracker)).err()
+    }
+
+    #[test]
+    fn default_tracker_config_is_valid() {
+        assert_eq!(TrackerConfig::default().validate(), Ok(()));
+        assert!(try_new_error(TrackerConfig::default()).is_none());
+    }
+
+    #[test]
+    fn try_new_rejects_each_invalid_tracker_setting() {
+        let kalman = KalmanConfig { gate: 0.0, ..KalmanConfig::default() };
+        let cases = [
+            (
+                TrackerConfig { frame_rate: 0.0, ..TrackerConfig::default() },
+                TrackerConfigError::InvalidFrameRate(0.0),
+            ),
+            (
+                TrackerConfig { coast_duration: -1.0, ..TrackerConfig::default() },
+                TrackerConfigError::InvalidDuration { field: "coast_duration", seconds: -1.0 },
+            ),
+            (
+                TrackerConfig { gate_distance: Distance::Normalized(0.0), ..TrackerConfig::default() },
+                TrackerConfigError::InvalidDistance { field: "gate_distance", distance: Distance::Normalized(0.0) },
+            ),
+            (
+                TrackerConfig { gate_growth: Distance::Pixels(-1.0), ..TrackerConfig::default() },
+                TrackerConfigError::InvalidDistance { field: "gate_growth", distance: Distance::Pixels(-1.0) },
+            ),
+            (
+                TrackerConfig {
+                    association_weights: AssociationWeights { overlap: -0.5, ..AssociationWeights::default() },
+                    ..TrackerConfig::default()
+                },
+                TrackerConfigError::InvalidWeight { field: "overlap", weight: -0.5 },
+            ),
+            (
+                TrackerConfig { motion_model: MotionModel::Kalman(kalman), ..TrackerConfig::default() },
+                TrackerConfigError::InvalidKalmanConfig(kalman),
+            ),
+            (
+                TrackerConfig { history_duration: 1.0 / 30.0, ..TrackerConfig::default() },
+                TrackerConfigError::HistoryTooShort { frames: 1 },
+            ),
+            (
+                TrackerConfig { behavioral_anomaly_threshold: -1.0, ..TrackerConfig::default() },
+                TrackerConfigError::InvalidAnomalyThreshold(-1.0),
+            ),
+        ];
+        for (tracker, error) in cases {
+            assert_eq!(try_new_error(tracker), Some(PipelineError::InvalidTrackerConfig(error)));
+        }
+    }
+
+    #[test]
+    fn zero_gate_growth_is_valid() {
+        let tracker = TrackerConfig { gate_growth: Distance::Normalized(0.0), ..TrackerConfig::default() };
+        assert!(try_new_error(tracker).is_none());
+    }
+
+    #[test]
+    #[should_panic(expected = "invalid tracker config: frame rate must be positive")]
+    fn new_panics_on_invalid_tracker_config() {
+        VisionPipeline::new(config(64, 48, TrackerConfig { frame_rate: -30.0, ..TrackerConfig::default() }));
+    }
+
+    /// A 32x24 frame of mid-gray flickering below the noise floor, with a bright square
+    /// from frame 25 on.
+    fn scene_frame(frame: u32) -> Vec<u8> {
+        let mut gray = Vec::with_capacity(32 * 24);
+        for y in 0..24u32 {
+            for x in 0..32u32 {
+                let inside = frame >= 25 && (8..14).contains(&x) && (8..14).contains(&y);
+                gray.push(if inside { 220 } else { 100 + ((x * 7 + y * 13 + frame * 5) % 2) as u8 });
+            }
+        }
+        gray
+    }
+
+    /// The status of every chunk, per frame, after running the scene through
+    /// `process_image` with the given encoding of each frame.
+    fn scene_statuses(config: PipelineConfig, encode: impl Fn(&[u8]) -> DynamicImage) -> Vec<Vec<ChunkStatus>> {
+        let mut pipeline = VisionPipeline::new(config);
+        (0..30)
+            .map(|frame| pipeline.process_image(&encode(&scene_frame(frame))).unwrap().status_map)
+            .collect()
+    }
+
+    fn assert_same_statuses(actual: &[Vec<ChunkStatus>], expected: &[Vec<ChunkStatus>]) {
+        for (frame, (actual, expected)) in actual.iter().zip(expected).enumerate() {
+            for (chunk, (actual, expected)) in actual.iter().zip(expected).enumerate() {
+                match (actual, expected) {
+                    (ChunkStatus::AnomalousEvent(a), ChunkStatus::AnomalousEvent(b)) => {
+                        assert!((a.luminance_score - b.luminance_score).abs() < 1e-6, "frame {frame}, chunk {chunk}");
+                    }
+                    _ => assert_eq!(actual, expected, "frame {frame}, chunk {chunk}"),
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn image_inputs_of_every_depth_give_the_same_analysis() {
+        use image::{GrayImage, ImageBuffer, Luma, Rgba};
+
+        let rgba8 = |gray: &[u8]| {
+            let bytes = gray.iter().flat_map(|&value| [value, value, value, 255]).collect();
+            DynamicImage::ImageRgba8(RgbaImage::from_raw(32, 24, bytes).unwrap())
+        };
+        let expected = scene_statuses(config(32, 24, TrackerConfig::default()), rgba8);
+        assert!(expected[25].iter().any(|status| matches!(status, ChunkStatus::AnomalousEvent(_))));
+
+        let luma8 = |gray: &[u8]| DynamicImage::ImageLuma8(GrayImage::from_raw(32, 24, gray.to_vec()).unwrap());
+        let luma16 = |gray: &[u8]| {
+            let samples = gray.iter().map(|&value| value as u16 * 257).collect();
+            DynamicImage::ImageLuma16(ImageBuffer::<Luma<u16>, _>::from_raw(32, 24, samples).unwrap())
+        };
+        let rgba16 = |gray: &[u8]| {
+            let samples = gray.iter().flat_map(|&value| [value as u16 * 257, value as u16 * 257, value as u16 * 257, u16::MAX]).collect();
+            DynamicImage::ImageRgba16(ImageBuffer::<Rgba<u16>, _>::from_raw(32, 24, samples).unwrap())
+        };
+        // 16-bit `image` buffers are full range whatever `high_bit_depth` says.
+        let misconfigured = PipelineConfig { high_bit_depth: BitDepth::Ten, ..config(32, 24, TrackerConfig::default()) };
+        assert_same_statuses(&scene_statuses(config(32, 24, TrackerConfig::default()), luma8), &expected);
+        assert_same_statuses(&scene_statuses(misconfigured.clone(), luma16), &expected);
+        assert_same_statuses(&scene_statuses(misconfigured, rgba16), &expected);
+    }
+
+    #[test]
+    fn process_image_rejects_other_dimensions() {
+        let mut pipeline = VisionPipeline::new(config(32, 24, TrackerConfig::default()));
+        let image = DynamicImage::ImageRgba8(RgbaImage::new(24, 32));
+        assert_eq!(
+            pipeline.process_image(&image).err(),
+            Some(PipelineError::DimensionMismatch { expected: (32, 24), actual: (24, 32) })
+        );
+    }
+
+    #[test]
+    fn encoded_frames_must_match_the_configured_size_after_scaling() {
+        let mut jpeg = std::io::Cursor::new(Vec::new());
+        image::RgbImage::new(32, 16).write_to(&mut jpeg, image::ImageFormat::Jpeg).unwrap();
+        let jpeg = jpeg.into_inner();
+        let half = |width, height| PipelineConfig {
+            jpeg_decode_scale: JpegScale::Half,
+            ..config(width, height, TrackerConfig::default())
+        };
+
+        let mut pipeline = VisionPipeline::new(half(32, 16));
+        assert_eq!(
+            pipeline.process_encoded_frame(&jpeg).err(),
+            Some(PipelineError::DimensionMismatch { expected: (32, 16), actual: (16, 8) })
+        );
+        let mut pipeline = VisionPipeline::new(half(16, 8));
+        assert_eq!(pipeline.process_encoded_frame(&jpeg).unwrap().status_map.len(), 16 * 8);
+        assert_eq!(
+            pipeline.process_encoded_frame(b"not an image").err(),
+            Some(PipelineError::Decode(DecodeError::UnrecognizedFormat))
+        );
+    }
+}